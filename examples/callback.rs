@@ -1,53 +1,62 @@
-﻿use poller::{EventContext, Events, Poller};
-use std::io::stdin;
-use std::sync::Arc;
+﻿#[cfg(target_os = "linux")]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    use poller::{Events, Poller, Timer};
+    use std::collections::HashMap;
+    use std::io::stdin;
+    use std::os::unix::io::AsRawFd;
+    use std::time::Duration;
 
-type Callback = fn() -> bool;
+    type Handler = Box<dyn FnMut(i32, Events) -> bool>;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Create the Poller.
     let mut poller = Poller::new()?;
-
-    // Callback for handle raised event.
-    let cb: Arc<Callback> = Arc::new(|| -> bool {
-        let mut input = String::new();
-        match stdin().read_line(&mut input) {
-            Ok(n) => {
-                let trimmed = input.trim_end();
-                println!("{} bytes readed: \"{}\"", n, trimmed);
-                // Return false if input 'q'.
-                trimmed != "q"
-            }
-            Err(e) => {
-                println!("error: {}", e);
-                false
-            }
-        }
-    });
+    let mut handlers: HashMap<i32, Handler> = HashMap::new();
 
     // Add stdin to the watching list of the Poller.
-    poller.add(
+    poller.add(0, Events::new().read(), None)?;
+    handlers.insert(
         0,
-        Events::new().read(),
-        Some(Arc::clone(&cb) as EventContext),
-    )?;
+        Box::new(|_fd, _events| {
+            let mut input = String::new();
+            match stdin().read_line(&mut input) {
+                Ok(n) => {
+                    let trimmed = input.trim_end();
+                    println!("{} bytes readed: \"{}\"", n, trimmed);
+                    // Return false if input 'q'.
+                    trimmed != "q"
+                }
+                Err(e) => {
+                    println!("error: {}", e);
+                    false
+                }
+            }
+        }),
+    );
+
+    // Add a periodic timer to the watching list of the Poller.
+    let timer = Timer::interval(Duration::from_secs(5))?;
+    poller.add_source(&timer, Events::new().read(), None)?;
+    handlers.insert(
+        timer.as_raw_fd(),
+        Box::new(move |_fd, _events| {
+            // Consume the timer expirations.
+            match timer.read_expirations() {
+                Ok(n) => println!("{} times 5 seconds elapsed", n),
+                Err(e) => println!("error: {}", e),
+            }
+            true
+        }),
+    );
 
     println!("Press ctrl+c or 'q' to exit ...");
 
-    'outer: loop {
-        // Pull all events with 1 seconds timeout.
-        let events = poller.pull_events(1000)?;
-        for (_fd, _events, _ctx) in events.iter() {
-            // Use EventContext to processing the event.
-            if let Some(x) = _ctx {
-                if let Some(cb) = x.downcast_ref::<Callback>() {
-                    if cb() == false {
-                        break 'outer;
-                    }
-                }
-            }
-        }
-    }
+    // Dispatch events to the handlers with 1 seconds timeout.
+    poller.run(&mut handlers, 1000)?;
 
     Ok(())
 }
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    println!("This example requires Linux.");
+}
@@ -0,0 +1,43 @@
+﻿#[cfg(all(target_os = "linux", not(feature = "backend-poll")))]
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    use poller::{Events, Poller, Timer};
+    use std::io::stdin;
+    use std::os::unix::io::AsRawFd;
+    use std::time::Duration;
+
+    // Create a child Poller for the console subsystem.
+    let mut console = Poller::new()?;
+    console.add(0, Events::new().read(), None)?;
+
+    // Create the parent Poller, watching the child and a periodic timer.
+    let mut poller = Poller::new()?;
+    poller.add_poller(&console, None)?;
+    let timer = Timer::interval(Duration::from_secs(5))?;
+    poller.add_source(&timer, Events::new().read(), None)?;
+
+    println!("Press ctrl+c or 'q' to exit ...");
+
+    loop {
+        for (fd, _events, _ctx) in poller.pull_events(1000)?.iter() {
+            if *fd == timer.as_raw_fd() {
+                println!("{} times 5 seconds elapsed", timer.read_expirations()?);
+            } else if *fd == console.as_raw_fd() {
+                // Drain the child Poller, otherwise it stays readable.
+                for _ in console.pull_events(0)?.iter() {
+                    let mut input = String::new();
+                    stdin().read_line(&mut input)?;
+                    let trimmed = input.trim_end();
+                    println!("{} bytes readed: \"{}\"", input.len(), trimmed);
+                    if trimmed == "q" {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(any(not(target_os = "linux"), feature = "backend-poll"))]
+fn main() {
+    println!("This example requires the epoll backend.");
+}
@@ -0,0 +1,67 @@
+﻿use poller::{Events, Poller};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::io::AsRawFd;
+use std::thread;
+use std::time::Duration;
+
+const TOTAL: usize = 8 * 1024 * 1024;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // A slow reader on the other end of the connection.
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let reader = thread::spawn(move || -> std::io::Result<usize> {
+        let (mut stream, _) = listener.accept()?;
+        let mut buf = [0u8; 64 * 1024];
+        let mut received = 0;
+        loop {
+            let n = stream.read(&mut buf)?;
+            if n == 0 {
+                return Ok(received);
+            }
+            received += n;
+            thread::sleep(Duration::from_millis(1));
+        }
+    });
+
+    // Connect a non-blocking socket and watch it for read (peer close) only.
+    let mut stream = TcpStream::connect(addr)?;
+    stream.set_nonblocking(true)?;
+    let fd = stream.as_raw_fd();
+    let mut poller = Poller::new()?;
+    poller.add(fd, Events::new().read(), None)?;
+
+    let data = vec![0x55u8; TOTAL];
+    let mut written = 0;
+    let mut stalls = 0;
+    while written < TOTAL {
+        match stream.write(&data[written..]) {
+            Ok(n) => written += n,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                // The send buffer is full, wait for it to drain.
+                stalls += 1;
+                poller.modify(fd, Events::new().read().write(), None)?;
+                loop {
+                    let events = poller.pull_events(1000)?;
+                    if events.iter().any(|(_, events, _)| events.has_write()) {
+                        break;
+                    }
+                }
+                // Turn write interest off again, otherwise the poller spins.
+                poller.modify(fd, Events::new().read(), None)?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    poller.remove(fd)?;
+    drop(stream);
+
+    let received = reader.join().unwrap()?;
+    println!(
+        "{} bytes written, {} bytes received, {} times blocked",
+        written, received, stalls
+    );
+
+    Ok(())
+}
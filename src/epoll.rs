@@ -1,10 +1,42 @@
 //! Linux 增强型 I/O 事件通知。
 //!
-use crate::{Events, SysError};
+#[cfg(feature = "std")]
+use crate::EventFd;
+use crate::{Event, EventHandler, Events, SysError};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::convert::TryFrom;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
 use libc::{close, epoll_create1, epoll_ctl, epoll_wait};
-use std::any::Any;
-use std::collections::HashMap;
-use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::{
+    future::Future,
+    os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd},
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll, Waker},
+    time::Instant,
+};
+
+/// 以 `fd` 为键的映射，用于 `reconcile` 及 `run`。
+///
+/// 启用 `std` 时为 `HashMap`，关闭时以 `BTreeMap` 代替。
+#[cfg(feature = "std")]
+pub type FdMap<V> = std::collections::HashMap<i32, V>;
+
+/// 以 `fd` 为键的映射，用于 `reconcile` 及 `run`。
+///
+/// 启用 `std` 时为 `HashMap`，关闭时以 `BTreeMap` 代替。
+#[cfg(not(feature = "std"))]
+pub type FdMap<V> = alloc::collections::BTreeMap<i32, V>;
+
+/// 内部使用的集合类型，与 `FdMap` 一样在关闭 `std` 时以 `BTreeSet` 代替。
+#[cfg(feature = "std")]
+type Set<T> = std::collections::HashSet<T>;
+#[cfg(not(feature = "std"))]
+type Set<T> = alloc::collections::BTreeSet<T>;
 
 impl From<u32> for Events {
     fn from(val: u32) -> Self {
@@ -18,26 +50,82 @@ impl From<u32> for Events {
         if (val & libc::EPOLLERR as u32) == libc::EPOLLERR as u32 {
             events = events.error();
         }
+        if (val & libc::EPOLLHUP as u32) == libc::EPOLLHUP as u32 {
+            events |= Events::from(Event::HangUp);
+        }
+        if (val & libc::EPOLLRDHUP as u32) == libc::EPOLLRDHUP as u32 {
+            events = events.read_hangup();
+        }
+        if (val & libc::EPOLLPRI as u32) == libc::EPOLLPRI as u32 {
+            events = events.priority();
+        }
+        if (val & libc::EPOLLONESHOT as u32) == libc::EPOLLONESHOT as u32 {
+            events = events.one_shot();
+        }
+        if (val & libc::EPOLLET as u32) == libc::EPOLLET as u32 {
+            events = events.edge_triggered();
+        }
+        if (val & libc::EPOLLEXCLUSIVE as u32) == libc::EPOLLEXCLUSIVE as u32 {
+            events = events.exclusive();
+        }
         events
     }
 }
 
-impl Into<u32> for Events {
-    fn into(self) -> u32 {
+impl From<Events> for u32 {
+    fn from(val: Events) -> Self {
         let mut events = 0u32;
-        if self.has_read() {
+        if val.has_read() {
             events |= libc::EPOLLIN as u32;
         }
-        if self.has_write() {
+        if val.has_write() {
             events |= libc::EPOLLOUT as u32;
         }
-        if self.has_error() {
+        if val.has_error() {
             events |= libc::EPOLLERR as u32;
         }
+        if val.has_hangup() {
+            events |= libc::EPOLLHUP as u32;
+        }
+        if val.has_read_hangup() {
+            events |= libc::EPOLLRDHUP as u32;
+        }
+        if val.has_priority() {
+            events |= libc::EPOLLPRI as u32;
+        }
+        if val.has_one_shot() {
+            events |= libc::EPOLLONESHOT as u32;
+        }
+        if val.has_edge_triggered() {
+            events |= libc::EPOLLET as u32;
+        }
+        if val.has_exclusive() {
+            events |= libc::EPOLLEXCLUSIVE as u32;
+        }
         events
     }
 }
 
+impl Events {
+    /// 转换为 `libc::EPOLL*` 标志，等同于 `u32::from(self)`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Events;
+    /// let flags = Events::new().read().write().to_epoll_flags();
+    /// assert_eq!(flags, (libc::EPOLLIN | libc::EPOLLOUT) as u32);
+    /// ```
+    pub fn to_epoll_flags(self) -> u32 {
+        u32::from(self)
+    }
+
+    /// 从 `libc::EPOLL*` 标志创建事件集合，等同于 `Events::from(flags)`。
+    pub fn from_epoll_flags(flags: u32) -> Events {
+        Events::from(flags)
+    }
+}
+
 /// 定义事件关联上下文。
 pub type EventContext = Arc<dyn Any + Send + Sync>;
 
@@ -47,148 +135,3494 @@ pub type EventContext = Arc<dyn Any + Send + Sync>;
 /// * `0` - 触发的文件描述符。
 /// * `1` - 触发的事件集合。
 /// * `2` - 触发的事件对应上下文。
-pub type EventData<'a> = (i32, Events, Option<&'a EventContext>);
+pub type EventData<'a, C = EventContext> = (i32, Events, Option<&'a C>);
+
+/// 定义带令牌的事件数据。
+///
+/// # Fields
+///
+/// * `0` - 注册时指定的令牌，未指定时为 `fd` 本身。
+/// * `1` - 触发的文件描述符。
+/// * `2` - 触发的事件集合。
+/// * `3` - 触发的事件对应上下文。
+pub type TokenEventData<'a, C = EventContext> = (u64, i32, Events, Option<&'a C>);
+
+/// 由内核事件构造的事件数据，`EventData` 会丢弃令牌，`TokenEventData` 则会保留。
+trait EventRecord<'a, C> {
+    fn new(token: u64, fd: i32, events: Events, ctx: Option<&'a C>) -> Self;
+}
+
+impl<'a, C> EventRecord<'a, C> for EventData<'a, C> {
+    fn new(_token: u64, fd: i32, events: Events, ctx: Option<&'a C>) -> Self {
+        (fd, events, ctx)
+    }
+}
 
-/// 定义文件 I/O 事件通知器。
+impl<'a, C> EventRecord<'a, C> for TokenEventData<'a, C> {
+    fn new(token: u64, fd: i32, events: Events, ctx: Option<&'a C>) -> Self {
+        (token, fd, events, ctx)
+    }
+}
+
+/// 定义自动移除的注册守卫。
+///
+/// 守卫销毁时会将对应的 `fd` 从 `Poller` 的监视列表中移除。守卫持有 `Poller` 的可变借用，
+/// 并通过 `Deref`/`DerefMut` 暴露 `Poller` 的全部方法，因此在守卫存活期间可以继续拉取事件或注册其它 `fd`。
 ///
-/// 每个实例可以管理多个 `fd` 的 I/O 事件。
+/// # Examples
+///
+/// ```
+/// use poller::{Events, Poller};
+/// let mut poller = Poller::new().unwrap();
+/// {
+///     let reg = poller.register(1, Events::new().write(), None).unwrap();
+///     assert_eq!(reg.pull_events(1000).unwrap().len(), 1);
+/// }
+/// assert!(poller.is_empty());
+/// ```
 #[derive(Debug)]
-pub struct Poller {
-    epoll_fd: i32,
-    watches: HashMap<i32, (Events, Option<EventContext>)>,
+pub struct Registration<'a, C = EventContext> {
+    poller: &'a mut TypedPoller<C>,
+    fd: i32,
 }
 
-impl Default for Poller {
-    fn default() -> Self {
-        Self {
-            epoll_fd: -1,
-            watches: HashMap::new(),
-        }
+impl<C> Registration<'_, C> {
+    /// 返回注册的文件描述符。
+    pub fn fd(&self) -> i32 {
+        self.fd
+    }
+}
+
+impl<C> core::ops::Deref for Registration<'_, C> {
+    type Target = TypedPoller<C>;
+
+    fn deref(&self) -> &TypedPoller<C> {
+        self.poller
+    }
+}
+
+impl<C> core::ops::DerefMut for Registration<'_, C> {
+    fn deref_mut(&mut self) -> &mut TypedPoller<C> {
+        self.poller
     }
 }
 
-impl Drop for Poller {
+impl<C> Drop for Registration<'_, C> {
     fn drop(&mut self) {
-        if self.epoll_fd > 0 {
-            unsafe {
-                close(self.epoll_fd);
-            };
-            self.epoll_fd = -1;
-        }
+        let _ = self.poller.remove(self.fd);
     }
 }
 
-impl Poller {
-    /// 创建一个新的 I/O 事件通知器。
-    pub fn new() -> Result<Self, SysError> {
-        let epoll_fd = unsafe { epoll_create1(0) };
-        if epoll_fd < 0 {
-            Err(SysError::last())
-        } else {
-            Ok(Self {
-                epoll_fd,
-                watches: HashMap::new(),
-            })
+/// 等待 `fd` 可读的 `Future`，由 `Poller::readable` 创建。
+///
+/// 首次轮询时以单次触发（`EPOLLONESHOT`）方式注册读事件，
+/// 需要有其它线程或任务调用 `pull_events` 驱动，事件触发后唤醒等待的任务。
+/// 在完成前被销毁时会自动取消注册。
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct Readable<'a, C = EventContext> {
+    poller: &'a TypedPoller<C>,
+    fd: i32,
+    registered: bool,
+}
+
+#[cfg(feature = "std")]
+impl<C> Future for Readable<'_, C> {
+    type Output = Result<Events, SysError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let fd = self.fd;
+        let mut readers = self.poller.readers.lock().unwrap();
+        if !self.registered {
+            if self.poller.watches.contains_key(fd) {
+                return Poll::Ready(Err(SysError::from(libc::EEXIST)));
+            }
+            let events = Events::new().read().one_shot();
+            if let Err(err) = self.poller.ctl(libc::EPOLL_CTL_ADD, fd, events) {
+                return Poll::Ready(Err(err));
+            }
+            readers.insert(fd, Reader::new(cx.waker().clone()));
+            drop(readers);
+            self.registered = true;
+            return Poll::Pending;
+        }
+        match readers.get_mut(&fd) {
+            Some(reader) => match reader.events {
+                Some(events) => {
+                    readers.remove(&fd);
+                    drop(readers);
+                    self.registered = false;
+                    let _ = self.poller.ctl_del(fd);
+                    Poll::Ready(Ok(events))
+                }
+                None => {
+                    reader.waker = cx.waker().clone();
+                    Poll::Pending
+                }
+            },
+            None => Poll::Ready(Err(SysError::from(libc::ENOENT))),
         }
     }
+}
 
-    /// 添加一个文件描述符到监视列表中。
-    ///
-    /// **注意：** 此函数不会把 `fd` 的所有权转移到 `Poller` 内，请确保在 `Poller` 活动期内 `fd` 都是可用的。
-    pub fn add(
-        &mut self,
-        fd: i32,
-        events: Events,
-        ctx: Option<EventContext>,
-    ) -> Result<(), SysError> {
-        unsafe {
-            let mut ev = libc::epoll_event {
-                events: events.into(),
-                u64: fd as u64,
-            };
-            let err = epoll_ctl(self.epoll_fd, libc::EPOLL_CTL_ADD, fd, &mut ev);
-            if err < 0 {
-                return Err(SysError::last());
+#[cfg(feature = "std")]
+impl<C> Drop for Readable<'_, C> {
+    fn drop(&mut self) {
+        if self.registered {
+            if let Ok(mut readers) = self.poller.readers.lock() {
+                if readers.remove(&self.fd).is_some() {
+                    let _ = self.poller.ctl_del(self.fd);
+                }
             }
-            self.watches.insert(fd, (events, ctx));
-            Ok(())
         }
     }
+}
 
-    /// 将一个文件描述符从监视列表中移除。
-    pub fn remove(&mut self, fd: i32) -> Result<(), SysError> {
-        if !self.watches.contains_key(&fd) {
-            return Err(SysError::from(libc::ENOENT));
-        }
-        let err =
-            unsafe { epoll_ctl(self.epoll_fd, libc::EPOLL_CTL_DEL, fd, std::ptr::null_mut()) };
-        if err < 0 {
-            Err(SysError::last())
-        } else {
-            self.watches.remove(&fd).unwrap();
-            Ok(())
-        }
+/// 一次拉取得到的一批事件，由 `Poller::pull_events` 等返回一批事件的方法返回。
+///
+/// 事件中的上下文借用自 `Poller` 的监视列表，因此其生命周期不能超过 `Poller` 本身。
+/// 可以直接用于 `for` 循环，也可以通过 `Deref` 当作切片使用（`len`、`iter`、下标等）。
+///
+/// # Examples
+///
+/// ```
+/// use poller::{Events, Poller};
+/// let mut poller = Poller::new().unwrap();
+/// poller.add(1, Events::new().write(), None).unwrap();
+/// let ready = poller.pull_events(1000).unwrap();
+/// assert_eq!(ready.len(), 1);
+/// for (fd, events, _ctx) in ready {
+///     assert_eq!((fd, events), (1, Events::new().write()));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Ready<'a, C = EventContext> {
+    events: Vec<EventData<'a, C>>,
+}
+
+impl<'a, C> Ready<'a, C> {
+    /// 转换为 `Vec`。
+    pub fn into_vec(self) -> Vec<EventData<'a, C>> {
+        self.events
     }
 
-    /// 拉取所有被监测到的 I/O 事件。
+    /// 返回不含上下文的事件迭代器，便于跨多次拉取收集或去重。
     ///
     /// # Examples
     ///
     /// ```
-    /// use poller::{Events, Poller};
+    /// use poller::{Events, Poller, ReadyEvent};
+    /// use std::collections::HashSet;
     /// let mut poller = Poller::new().unwrap();
     /// poller.add(1, Events::new().write(), None).unwrap();
-    /// for (fd, events, ctx) in poller.pull_events(1000).unwrap().iter() {
-    ///     println!("Fd={}, Events={}, Context={:?}", fd, events, ctx);
+    /// let mut seen = HashSet::new();
+    /// for _ in 0..3 {
+    ///     seen.extend(poller.pull_events(1000).unwrap().ready_events());
     /// }
+    /// assert_eq!(seen.len(), 1);
+    /// assert!(seen.contains(&ReadyEvent { fd: 1, events: Events::new().write() }));
     /// ```
-    pub fn pull_events(&self, timeout_ms: i32) -> Result<Vec<EventData>, SysError> {
-        unsafe {
-            let mut ev: Vec<libc::epoll_event> = Vec::with_capacity(self.watches.len());
-            let nfds = epoll_wait(
-                self.epoll_fd,
-                ev.as_mut_ptr(),
-                self.watches.len() as i32,
-                timeout_ms,
-            );
-            if nfds < 0 {
-                return Err(SysError::last());
-            }
-            ev.set_len(nfds as usize);
-            Ok(ev
-                .into_iter()
-                .map(|x| {
-                    if let Some(v) = self.watches.get(&(x.u64 as i32)) {
-                        (x.u64 as i32, Events::from(x.events), v.1.as_ref())
-                    } else {
-                        (x.u64 as i32, Events::from(x.events), None)
-                    }
-                })
-                .collect())
+    pub fn ready_events(&self) -> impl Iterator<Item = ReadyEvent> + '_ {
+        self.events.iter().map(ReadyEvent::from)
+    }
+}
+
+impl<'a, C> core::ops::Deref for Ready<'a, C> {
+    type Target = [EventData<'a, C>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.events
+    }
+}
+
+impl<'a, C> IntoIterator for Ready<'a, C> {
+    type Item = EventData<'a, C>;
+    type IntoIter = alloc::vec::IntoIter<EventData<'a, C>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.events.into_iter()
+    }
+}
+
+impl<'r, 'a, C> IntoIterator for &'r Ready<'a, C> {
+    type Item = &'r EventData<'a, C>;
+    type IntoIter = core::slice::Iter<'r, EventData<'a, C>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.events.iter()
+    }
+}
+
+impl<'a, C: PartialEq> PartialEq<Vec<EventData<'a, C>>> for Ready<'a, C> {
+    fn eq(&self, other: &Vec<EventData<'a, C>>) -> bool {
+        &self.events == other
+    }
+}
+
+/// 不含上下文的就绪事件，可以作为集合或映射的键。
+///
+/// 排序时先比较 `fd`，再比较事件集合的原始位。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ReadyEvent {
+    /// 就绪的文件描述符。
+    pub fd: i32,
+    /// 触发的事件集合。
+    pub events: Events,
+}
+
+impl<'a, C> From<&EventData<'a, C>> for ReadyEvent {
+    fn from(data: &EventData<'a, C>) -> Self {
+        Self {
+            fd: data.0,
+            events: data.1,
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl Ord for ReadyEvent {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (self.fd, self.events.0).cmp(&(other.fd, other.events.0))
+    }
+}
 
-    #[test]
-    fn test_poller() {
-        unsafe {
-            let cstr = std::ffi::CString::new("/proc/uptime").unwrap();
-            let fd = libc::open(cstr.as_ptr(), libc::O_RDONLY);
-            let mut poller = Poller::new().unwrap();
-            assert_eq!(poller.add(fd, Events::new().read(), None).is_ok(), true);
-            for _ in 0..1000 {
-                assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+impl PartialOrd for ReadyEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 一次等待的结果，由 `Poller::pull_events_status` 返回。
+#[derive(Debug)]
+pub enum WaitStatus<'a, C = EventContext> {
+    /// 等待超时，期间内核没有报告任何事件。
+    Timeout,
+    /// 内核报告了事件，其中的列表可能为空（例如仅被 `wake` 唤醒）。
+    Events(Ready<'a, C>),
+}
+
+/// 可取消的等待的结果，由 `Poller::pull_events_cancellable` 返回。
+#[derive(Debug)]
+pub enum CancellableStatus<'a, C = EventContext> {
+    /// 等待超时，期间内核没有报告任何事件。
+    Timeout,
+    /// 等待被取消标志中止。
+    Cancelled,
+    /// 内核报告了事件，其中的列表可能为空（例如仅被 `wake` 唤醒）。
+    Events(Ready<'a, C>),
+}
+
+/// 持续拉取事件的阻塞迭代器，由 `Poller::events` 创建。
+///
+/// 当前批次的事件耗尽后会再次调用 `pull_events`，超时没有事件时继续等待。
+/// `pull_events` 出错时迭代结束，可通过 `last_error` 取得导致结束的错误。
+#[derive(Debug)]
+pub struct EventIter<'a, C = EventContext> {
+    poller: &'a TypedPoller<C>,
+    timeout_ms: i32,
+    batch: alloc::vec::IntoIter<EventData<'a, C>>,
+    error: Option<SysError>,
+}
+
+impl<C> EventIter<'_, C> {
+    /// 返回导致迭代结束的错误，迭代尚未因错误结束时返回 `None`。
+    pub fn last_error(&self) -> Option<SysError> {
+        self.error
+    }
+}
+
+impl<'a, C> Iterator for EventIter<'a, C> {
+    type Item = EventData<'a, C>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(x) = self.batch.next() {
+                return Some(x);
             }
-            assert_eq!(poller.remove(fd).is_ok(), true);
-            for _ in 0..1000 {
-                assert_eq!(poller.add(fd, Events::new().read(), None).is_ok(), true);
-                assert_eq!(poller.remove(fd).is_ok(), true);
+            if self.error.is_some() {
+                return None;
             }
-            libc::close(fd);
+            match self.poller.pull_events(self.timeout_ms) {
+                Ok(batch) => self.batch = batch.into_iter(),
+                Err(err) => self.error = Some(err),
+            }
+        }
+    }
+}
+
+/// 定义等待可读的 `fd` 的状态。
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct Reader {
+    events: Option<Events>,
+    waker: Waker,
+}
+
+#[cfg(feature = "std")]
+impl Reader {
+    fn new(waker: Waker) -> Self {
+        Self {
+            events: None,
+            waker,
+        }
+    }
+}
+
+/// 定义 `Poller` 的统计数据。
+#[cfg(feature = "stats")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PollerStats {
+    /// 调用 `epoll_wait` 的总次数。
+    pub waits: u64,
+    /// 返回给调用者的事件总数。
+    pub events: u64,
+    /// 超时且没有任何事件的等待次数。
+    pub timeouts: u64,
+}
+
+/// 定义 `Poller` 内部累计的统计计数器。
+#[cfg(feature = "stats")]
+#[derive(Debug, Default)]
+struct Stats {
+    waits: core::sync::atomic::AtomicU64,
+    events: core::sync::atomic::AtomicU64,
+    timeouts: core::sync::atomic::AtomicU64,
+}
+
+/// 定义监视列表中的一项。
+#[derive(Debug)]
+struct Watch<C> {
+    events: Events,
+    disabled: bool,
+    token: u64,
+    ctx: Option<C>,
+    #[cfg(feature = "std")]
+    owned: Option<OwnedFd>,
+}
+
+impl<C> Watch<C> {
+    fn new(events: Events, token: u64, ctx: Option<C>) -> Self {
+        Self {
+            events,
+            disabled: false,
+            token,
+            ctx,
+            #[cfg(feature = "std")]
+            owned: None,
+        }
+    }
+}
+
+impl<C: Clone> Clone for Watch<C> {
+    /// 克隆的监视项不拥有 `fd` 的所有权，`fd` 仍由原监视项负责关闭。
+    fn clone(&self) -> Self {
+        Self {
+            events: self.events,
+            disabled: self.disabled,
+            token: self.token,
+            ctx: self.ctx.clone(),
+            #[cfg(feature = "std")]
+            owned: None,
+        }
+    }
+}
+
+/// 定义以 `fd` 为下标的监视列表。
+///
+/// `fd` 是较小且密集的整数，直接作为 `Vec` 的下标可以避免每次查找时计算哈希。
+/// 内核事件数据中保存的是 `fd`，应用层令牌保存在监视项内。
+#[derive(Debug)]
+struct WatchTable<C> {
+    slots: Vec<Option<Watch<C>>>,
+    len: usize,
+}
+
+impl<C> Default for WatchTable<C> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<C: Clone> Clone for WatchTable<C> {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<C> WatchTable<C> {
+    fn reserve(&mut self, additional: usize) {
+        self.slots.reserve(additional);
+    }
+
+    fn contains_key(&self, fd: i32) -> bool {
+        self.get(fd).is_some()
+    }
+
+    fn get(&self, fd: i32) -> Option<&Watch<C>> {
+        let index = usize::try_from(fd).ok()?;
+        self.slots.get(index).and_then(Option::as_ref)
+    }
+
+    fn get_mut(&mut self, fd: i32) -> Option<&mut Watch<C>> {
+        let index = usize::try_from(fd).ok()?;
+        self.slots.get_mut(index).and_then(Option::as_mut)
+    }
+
+    fn insert(&mut self, fd: i32, watch: Watch<C>) {
+        let index = usize::try_from(fd).expect("negative fd");
+        if index >= self.slots.len() {
+            self.slots.resize_with(index + 1, || None);
+        }
+        if self.slots[index].replace(watch).is_none() {
+            self.len += 1;
+        }
+    }
+
+    fn remove(&mut self, fd: i32) -> Option<Watch<C>> {
+        let index = usize::try_from(fd).ok()?;
+        let watch = self.slots.get_mut(index)?.take()?;
+        self.len -= 1;
+        Some(watch)
+    }
+
+    fn clear(&mut self) {
+        self.slots.clear();
+        self.len = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (i32, &Watch<C>)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(fd, slot)| slot.as_ref().map(|watch| (fd as i32, watch)))
+    }
+}
+
+/// 定义文件 I/O 事件通知器，可以为每个 `fd` 关联任意类型的上下文。
+///
+/// 这是上下文类型为 `EventContext` 的 `TypedPoller`，取出上下文后需要通过 `downcast_ref` 转换为具体类型。
+pub type Poller = TypedPoller<EventContext>;
+
+/// 定义上下文类型为 `C` 的文件 I/O 事件通知器。
+///
+/// 每个实例可以管理多个 `fd` 的 I/O 事件。只需要关联一种上下文类型时，使用 `TypedPoller<C>`
+/// 可以直接得到 `Option<&C>`，无需再进行 `downcast_ref` 转换。
+///
+/// # Examples
+///
+/// ```
+/// use poller::{Events, TypedPoller};
+/// let mut poller = TypedPoller::<&str>::new().unwrap();
+/// poller.add(1, Events::new().write(), Some("stdout")).unwrap();
+/// for (fd, _events, ctx) in poller.pull_events(1000).unwrap() {
+///     assert_eq!((fd, ctx), (1, Some(&"stdout")));
+/// }
+/// ```
+///
+/// # 线程安全
+///
+/// `Poller` 实现了 `Send` 和 `Sync`：
+///
+/// * `pull_events` 系列方法和 `wake` 只需要 `&self`，可以通过 `Arc<Poller>` 在多个线程中同时调用；
+/// * `add`、`modify`、`remove` 等修改监视列表的方法需要 `&mut self`，由借用规则保证它们不会与
+///   `pull_events` 并发执行。需要在等待的同时修改监视列表时，可以使用 `RwLock<Poller>` 并在
+///   获取写锁前调用 `wake` 让等待线程释放读锁。
+#[derive(Debug)]
+pub struct TypedPoller<C> {
+    epoll_fd: i32,
+    #[cfg(feature = "std")]
+    waker: Option<Arc<EventFd>>,
+    watches: WatchTable<C>,
+    #[cfg(feature = "std")]
+    readers: Mutex<FdMap<Reader>>,
+    #[cfg(feature = "std")]
+    scratch: Mutex<Vec<libc::epoll_event>>,
+    max_events: usize,
+    #[cfg(feature = "stats")]
+    stats: Stats,
+}
+
+/// 默认每次 `epoll_wait` 最多返回的事件数量。
+const DEFAULT_MAX_EVENTS: usize = 1024;
+
+/// `pull_events_cancellable` 每次等待的最长时间，即检查取消标志的间隔。
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 记录内核是否不支持 `epoll_pwait2`（Linux 5.11 之前），避免每次调用都重新探测。
+static PWAIT2_UNSUPPORTED: AtomicBool = AtomicBool::new(false);
+
+impl<C> Default for TypedPoller<C> {
+    fn default() -> Self {
+        Self {
+            epoll_fd: -1,
+            #[cfg(feature = "std")]
+            waker: None,
+            watches: WatchTable::default(),
+            #[cfg(feature = "std")]
+            readers: Mutex::new(FdMap::new()),
+            #[cfg(feature = "std")]
+            scratch: Mutex::new(Vec::new()),
+            max_events: DEFAULT_MAX_EVENTS,
+            #[cfg(feature = "stats")]
+            stats: Stats::default(),
+        }
+    }
+}
+
+impl<C> Drop for TypedPoller<C> {
+    fn drop(&mut self) {
+        if self.epoll_fd >= 0 {
+            unsafe {
+                close(self.epoll_fd);
+            };
+            self.epoll_fd = -1;
         }
     }
 }
+
+#[cfg(feature = "std")]
+impl<C> AsRawFd for TypedPoller<C> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.epoll_fd
+    }
+}
+
+/// 从一个已有的 epoll 文件描述符创建 `Poller`，并取得其所有权。
+///
+/// **注意：** 调用者必须保证 `fd` 是一个有效的 epoll 实例，`Poller` 不会进行任何检查；
+/// 新建的 `Poller` 监视列表为空，不会感知 `fd` 上已有的注册。
+/// 创建时会向 `fd` 注册一个新的唤醒用 eventfd，若创建或注册失败，`wake` 会返回 `EBADF` 错误。
+#[cfg(feature = "std")]
+impl<C> FromRawFd for TypedPoller<C> {
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        let mut poller = Self::default();
+        poller.epoll_fd = fd;
+        let _ = poller.register_waker();
+        poller
+    }
+}
+
+/// 交出 epoll 文件描述符的所有权，`Poller` 销毁时不会关闭它。
+#[cfg(feature = "std")]
+impl<C> IntoRawFd for TypedPoller<C> {
+    fn into_raw_fd(mut self) -> RawFd {
+        let fd = self.epoll_fd;
+        self.epoll_fd = -1;
+        fd
+    }
+}
+
+impl<C> TypedPoller<C> {
+    /// 创建一个新的 I/O 事件通知器。
+    ///
+    /// 内部的 epoll 文件描述符带有 `EPOLL_CLOEXEC` 标志，不会泄露到 `exec()` 后的子进程中。
+    pub fn new() -> Result<Self, SysError> {
+        Self::new_with_flags(libc::EPOLL_CLOEXEC)
+    }
+
+    /// 创建一个新的 I/O 事件通知器，并为 `n` 个 `fd` 预先分配监视列表及事件缓冲区的空间。
+    ///
+    /// 监视列表以 `fd` 为下标，预分配的空间覆盖编号小于 `n` 的 `fd`；
+    /// 事件缓冲区的预分配大小不超过每次拉取最多返回的事件数量。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Poller;
+    /// let poller = Poller::with_capacity(10000).unwrap();
+    /// ```
+    pub fn with_capacity(n: usize) -> Result<Self, SysError> {
+        let mut poller = Self::new()?;
+        poller.watches.reserve(n);
+        #[cfg(feature = "std")]
+        if let Ok(scratch) = poller.scratch.get_mut() {
+            scratch.reserve((n + 1).min(poller.max_events));
+        }
+        Ok(poller)
+    }
+
+    /// 使用指定的 `epoll_create1` 标志创建一个新的 I/O 事件通知器。
+    ///
+    /// 传入 `0` 可获得不带 `EPOLL_CLOEXEC` 的旧行为。
+    pub fn new_with_flags(flags: i32) -> Result<Self, SysError> {
+        let epoll_fd = unsafe { epoll_create1(flags) };
+        if epoll_fd < 0 {
+            return Err(SysError::last().with_context("epoll_create1"));
+        }
+        let mut poller = Self::default();
+        poller.epoll_fd = epoll_fd;
+        #[cfg(feature = "std")]
+        poller.register_waker()?;
+        Ok(poller)
+    }
+
+    /// 创建唤醒用的 eventfd 并注册到 epoll 实例中。
+    #[cfg(feature = "std")]
+    fn register_waker(&mut self) -> Result<(), SysError> {
+        let waker = EventFd::new(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK)?;
+        self.ctl(libc::EPOLL_CTL_ADD, waker.as_raw_fd(), Events::new().read())?;
+        self.waker = Some(Arc::new(waker));
+        Ok(())
+    }
+
+    /// 设置每次拉取最多返回的事件数量，默认为 1024。
+    ///
+    /// 超出部分会留在内核中，由下一次拉取返回。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Poller;
+    /// let poller = Poller::new().unwrap().with_max_events(64);
+    /// ```
+    pub fn with_max_events(mut self, n: usize) -> Self {
+        self.max_events = n.max(1);
+        self
+    }
+
+    /// 创建一个与当前 `Poller` 共享同一个内核 epoll 对象的新句柄。
+    ///
+    /// 新句柄使用 `F_DUPFD_CLOEXEC` 复制的 epoll 文件描述符，并复制一份当前的监视列表；
+    /// 由于共享内核对象，通过任一句柄添加或移除的注册在内核中对另一个句柄同样可见，
+    /// 但各自的监视列表（上下文、令牌等）是独立维护的。
+    /// 通过 `add_owned` 转移到 `Poller` 内的 `fd` 仍只由原句柄负责关闭。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().write(), None).unwrap();
+    /// let monitor = poller.try_clone().unwrap();
+    /// assert_eq!(monitor.pull_events(1000).unwrap().len(), 1);
+    /// ```
+    pub fn try_clone(&self) -> Result<Self, SysError>
+    where
+        C: Clone,
+    {
+        let epoll_fd = unsafe { libc::fcntl(self.epoll_fd, libc::F_DUPFD_CLOEXEC, 0) };
+        if epoll_fd < 0 {
+            return Err(SysError::last().with_context("fcntl F_DUPFD_CLOEXEC"));
+        }
+        let mut poller = Self::default();
+        poller.epoll_fd = epoll_fd;
+        #[cfg(feature = "std")]
+        {
+            poller.waker = self.waker.clone();
+        }
+        poller.watches = self.watches.clone();
+        poller.max_events = self.max_events;
+        Ok(poller)
+    }
+
+    /// 添加一个文件描述符到监视列表中。
+    ///
+    /// 如果 `fd` 已经在监视列表中，则按照 `modify` 的规则更新其事件集合及上下文（保留边沿触发模式，
+    /// 包含独占唤醒标志时返回 `EINVAL` 错误），而不会返回 `EEXIST` 错误；
+    /// `fd` 编号被复用导致内核中已没有对应注册时会重新添加。
+    ///
+    /// 同一个 `fd` 可以分别添加到多个 `Poller` 中，例如在一个 `Poller` 中监视读事件、在另一个中监视写事件，
+    /// 每个 `Poller` 独立地报告自己关心的事件；但在同一个 `Poller` 中一个 `fd` 只能有一组事件集合。
+    ///
+    /// `events` 不含任何读、写、紧急数据或对端关闭写入事件（例如只有挂起、错误、边沿触发等标志）时
+    /// 返回上下文为 `"empty interest mask"` 的 `EINVAL` 错误，以区别于内核返回的 `EINVAL`。
+    /// 挂起及错误事件总会被内核报告，单独注册它们没有意义。
+    ///
+    /// `fd` 必须为非负数，调试构建下传入负数会触发断言；`fd` 本身会被用作令牌，参见 `add_with_token`。
+    ///
+    /// **注意：** 此函数不会把 `fd` 的所有权转移到 `Poller` 内，请确保在 `Poller` 活动期内 `fd` 都是可用的。
+    pub fn add(&mut self, fd: i32, events: Events, ctx: Option<C>) -> Result<(), SysError> {
+        self.add_with_token(fd, events, fd as u64, ctx)
+    }
+
+    /// 批量添加文件描述符到监视列表中，并返回每个 `fd` 各自的添加结果。
+    ///
+    /// 某个 `fd` 添加失败时不会中止，其余的 `fd` 仍会继续添加，便于调用者记录并跳过错误的 `fd`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// let results = poller.add_batch(vec![(1, Events::new().write(), None), (i32::MAX, Events::new().read(), None)]);
+    /// assert!(results[0].1.is_ok());
+    /// assert_eq!(results[1].0, i32::MAX);
+    /// assert!(results[1].1.is_err());
+    /// ```
+    pub fn add_batch(
+        &mut self,
+        items: impl IntoIterator<Item = (i32, Events, Option<C>)>,
+    ) -> Vec<(i32, Result<(), SysError>)> {
+        items
+            .into_iter()
+            .map(|(fd, events, ctx)| (fd, self.add(fd, events, ctx)))
+            .collect()
+    }
+
+    /// 添加一个文件描述符到监视列表中，并为其指定一个应用层令牌。
+    ///
+    /// 令牌与 `fd` 编号无关，会保存在内核事件数据（`epoll_event.u64`）中并通过 `pull_events_with_tokens`
+    /// 随事件一起返回，可以直接作为调用者自己的索引使用，无需再维护 `fd` 到对象的映射。
+    ///
+    /// 令牌只是随事件返回的数据，不要求唯一，也不会与其它 `fd` 的编号冲突；
+    /// 多个 `fd` 使用同一个令牌时需要调用者自行通过事件中的 `fd` 区分。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add_with_token(1, Events::new().write(), 42, None).unwrap();
+    /// for (token, fd, _events, _ctx) in poller.pull_events_with_tokens(1000).unwrap() {
+    ///     assert_eq!((token, fd), (42, 1));
+    /// }
+    /// ```
+    pub fn add_with_token(
+        &mut self,
+        fd: i32,
+        events: Events,
+        token: u64,
+        ctx: Option<C>,
+    ) -> Result<(), SysError> {
+        debug_assert!(fd >= 0, "negative fd {}", fd);
+        let events = if self.watches.contains_key(fd) {
+            self.modified_events(fd, events)?
+        } else {
+            check_interest(events)?;
+            events
+        };
+        let (op, fallback, fallback_err) = if self.watches.contains_key(fd) {
+            (libc::EPOLL_CTL_MOD, libc::EPOLL_CTL_ADD, libc::ENOENT)
+        } else {
+            (libc::EPOLL_CTL_ADD, libc::EPOLL_CTL_MOD, libc::EEXIST)
+        };
+        match self.ctl(op, fd, events) {
+            Err(err) if err.code() == fallback_err => self.ctl(fallback, fd, events)?,
+            result => result?,
+        }
+        match self.watches.get_mut(fd) {
+            Some(watch) => {
+                // 重新添加会以新的事件集合修改内核中的注册，因此同时恢复被 `disable` 暂停的 `fd`。
+                watch.events = events;
+                watch.disabled = false;
+                watch.ctx = ctx;
+                watch.token = token;
+            }
+            None => {
+                self.watches.insert(fd, Watch::new(events, token, ctx));
+            }
+        }
+        Ok(())
+    }
+
+    /// 添加一个文件描述符到监视列表中，并将其所有权转移到 `Poller` 内。
+    ///
+    /// `fd` 会在被移除或 `Poller` 销毁时自动关闭；添加失败时也会被立即关闭。
+    #[cfg(feature = "std")]
+    pub fn add_owned(
+        &mut self,
+        fd: OwnedFd,
+        events: Events,
+        ctx: Option<C>,
+    ) -> Result<(), SysError> {
+        let raw_fd = fd.as_raw_fd();
+        check_interest(events)?;
+        self.ctl(libc::EPOLL_CTL_ADD, raw_fd, events)?;
+        let mut watch = Watch::new(events, raw_fd as u64, ctx);
+        watch.owned = Some(fd);
+        self.watches.insert(raw_fd, watch);
+        Ok(())
+    }
+
+    /// 复制 `fd`（`F_DUPFD_CLOEXEC`）并将副本添加到监视列表中，返回副本的文件描述符。
+    ///
+    /// 副本归 `Poller` 所有，会在被移除或 `Poller` 销毁时自动关闭，调用者可以随时关闭原来的 `fd`。
+    /// 事件以副本的 `fd` 报告，移除时也需要使用返回的副本。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// let fd = poller.add_dup(1, Events::new().write(), None).unwrap();
+    /// assert_ne!(fd, 1);
+    /// assert_eq!(poller.pull_events(1000).unwrap()[0].0, fd);
+    /// poller.remove(fd).unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn add_dup(&mut self, fd: i32, events: Events, ctx: Option<C>) -> Result<i32, SysError> {
+        let dup = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) };
+        if dup < 0 {
+            return Err(SysError::last().with_context("fcntl F_DUPFD_CLOEXEC"));
+        }
+        self.add_owned(unsafe { OwnedFd::from_raw_fd(dup) }, events, ctx)?;
+        Ok(dup)
+    }
+
+    /// 添加一个文件描述符到监视列表中，并返回一个在销毁时自动移除该 `fd` 的守卫。
+    pub fn register(
+        &mut self,
+        fd: i32,
+        events: Events,
+        ctx: Option<C>,
+    ) -> Result<Registration<'_, C>, SysError> {
+        self.add(fd, events, ctx)?;
+        Ok(Registration { poller: self, fd })
+    }
+
+    /// 添加一个实现了 `AsRawFd` 的对象到监视列表中。
+    ///
+    /// **注意：** 与 `add` 相同，`Poller` 不会持有 `src`，请确保在 `Poller` 活动期内 `src` 都是可用的。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add_source(&std::io::stdout(), Events::new().write(), None).unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn add_source<T: AsRawFd>(
+        &mut self,
+        src: &T,
+        events: Events,
+        ctx: Option<C>,
+    ) -> Result<(), SysError> {
+        self.add(src.as_raw_fd(), events, ctx)
+    }
+
+    /// 将另一个 `Poller` 添加到监视列表中，用于构建分层的事件循环。
+    ///
+    /// 子 `Poller` 监视的任意 `fd` 就绪时，其 epoll 文件描述符就会变为可读。
+    ///
+    /// **注意：** 父 `Poller` 报告子 `Poller` 可读后，必须调用子 `Poller` 的 `pull_events(0)`
+    /// 取出实际的事件，否则它会一直保持可读状态。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut child = Poller::new().unwrap();
+    /// child.add(1, Events::new().write(), None).unwrap();
+    /// let mut parent = Poller::new().unwrap();
+    /// parent.add_poller(&child, None).unwrap();
+    /// assert_eq!(parent.pull_events(1000).unwrap().len(), 1);
+    /// assert_eq!(child.pull_events(0).unwrap().len(), 1);
+    /// ```
+    pub fn add_poller<D>(
+        &mut self,
+        child: &TypedPoller<D>,
+        ctx: Option<C>,
+    ) -> Result<(), SysError> {
+        self.add(child.epoll_fd, Events::new().read(), ctx)
+    }
+
+    /// 修改一个已在监视列表中的文件描述符的事件集合及上下文。
+    ///
+    /// 如果 `fd` 不在监视列表中则返回 `ENOENT` 错误；`events` 包含独占唤醒标志或不含任何读、写、紧急数据或对端关闭写入事件时返回 `EINVAL` 错误。
+    ///
+    /// `fd` 已处于边沿触发模式时会保留该模式，需要切换回水平触发时请调用 `set_edge_triggered`。
+    pub fn modify(&mut self, fd: i32, events: Events, ctx: Option<C>) -> Result<(), SysError> {
+        let events = self.modified_events(fd, events)?;
+        self.ctl(libc::EPOLL_CTL_MOD, fd, events)?;
+        let watch = self.watches.get_mut(fd).unwrap();
+        watch.events = events;
+        watch.disabled = false;
+        watch.ctx = ctx;
+        Ok(())
+    }
+
+    /// 按照 `modify` 的规则计算 `fd` 修改后的事件集合：保留边沿触发模式，拒绝独占唤醒标志及空的关注事件。
+    fn modified_events(&self, fd: i32, events: Events) -> Result<Events, SysError> {
+        let events = match self.watches.get(fd) {
+            Some(watch) if watch.events.has_edge_triggered() => events.edge_triggered(),
+            Some(_) => events,
+            None => return Err(SysError::from(libc::ENOENT)),
+        };
+        if events.has_exclusive() {
+            return Err(SysError::from(libc::EINVAL));
+        }
+        check_interest(events)?;
+        Ok(events)
+    }
+
+    /// 确保 `fd` 以指定的事件集合及上下文出现在监视列表中。
+    ///
+    /// `fd` 已在监视列表中时调用 `modify`（保留其令牌），否则调用 `add`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.modify_or_add(1, Events::new().write(), None).unwrap();
+    /// poller.modify_or_add(1, Events::new().read().write(), None).unwrap();
+    /// assert_eq!(poller.events_for(1), Some(Events::new().read().write()));
+    /// ```
+    pub fn modify_or_add(
+        &mut self,
+        fd: i32,
+        events: Events,
+        ctx: Option<C>,
+    ) -> Result<(), SysError> {
+        if self.watches.contains_key(fd) {
+            self.modify(fd, events, ctx)
+        } else {
+            self.add(fd, events, ctx)
+        }
+    }
+
+    /// 切换 `fd` 的边沿触发（`true`）或水平触发（`false`）模式，保持其它事件不变。
+    ///
+    /// `fd` 已被 `disable` 暂停时只更新保存的事件集合，由 `enable` 生效。
+    /// 如果 `fd` 不在监视列表中则返回 `ENOENT` 错误。
+    pub fn set_edge_triggered(&mut self, fd: i32, edge: bool) -> Result<(), SysError> {
+        let (events, disabled) = match self.watches.get(fd) {
+            Some(watch) if edge => (watch.events.edge_triggered(), watch.disabled),
+            Some(watch) => (watch.events.without_edge_triggered(), watch.disabled),
+            None => return Err(SysError::from(libc::ENOENT)),
+        };
+        if !disabled {
+            self.ctl(libc::EPOLL_CTL_MOD, fd, events)?;
+        }
+        self.watches.get_mut(fd).unwrap().events = events;
+        Ok(())
+    }
+
+    /// 开启（`true`）或关闭（`false`）`fd` 的可写事件，返回是否实际调用了 `epoll_ctl`。
+    ///
+    /// 只有保存的事件集合确实发生变化时才会修改内核中的注册，适合在写缓冲区反复填满、
+    /// 排空的热路径中调用；`fd` 已被 `disable` 暂停时只更新保存的事件集合，由 `enable` 生效。
+    /// 如果 `fd` 不在监视列表中则返回 `ENOENT` 错误。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().read(), None).unwrap();
+    /// assert_eq!(poller.set_write_interest(1, true), Ok(true));
+    /// assert_eq!(poller.set_write_interest(1, true), Ok(false));
+    /// assert_eq!(poller.events_for(1), Some(Events::new().read().write()));
+    /// ```
+    pub fn set_write_interest(&mut self, fd: i32, enabled: bool) -> Result<bool, SysError> {
+        let (events, disabled) = match self.watches.get(fd) {
+            Some(watch) if watch.events.has_write() == enabled => return Ok(false),
+            Some(watch) if enabled => (watch.events.write(), watch.disabled),
+            Some(watch) => (watch.events.without_write(), watch.disabled),
+            None => return Err(SysError::from(libc::ENOENT)),
+        };
+        check_interest(events)?;
+        if !disabled {
+            self.ctl(libc::EPOLL_CTL_MOD, fd, events)?;
+        }
+        self.watches.get_mut(fd).unwrap().events = events;
+        Ok(!disabled)
+    }
+
+    /// 暂停监视 `fd` 的事件，但保留其事件集合及上下文，之后可以通过 `enable` 恢复。
+    ///
+    /// 内核仍然会报告无法屏蔽的挂起（`EPOLLHUP`）及错误（`EPOLLERR`）事件。
+    /// 如果 `fd` 不在监视列表中则返回 `ENOENT` 错误。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().write(), None).unwrap();
+    /// poller.disable(1).unwrap();
+    /// assert!(poller.pull_events(0).unwrap().is_empty());
+    /// poller.enable(1).unwrap();
+    /// assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+    /// ```
+    pub fn disable(&mut self, fd: i32) -> Result<(), SysError> {
+        if !self.watches.contains_key(fd) {
+            return Err(SysError::from(libc::ENOENT));
+        }
+        self.ctl(libc::EPOLL_CTL_MOD, fd, Events::new())?;
+        self.watches.get_mut(fd).unwrap().disabled = true;
+        Ok(())
+    }
+
+    /// 使用已保存的事件集合恢复监视被 `disable` 暂停的 `fd`。
+    ///
+    /// 如果 `fd` 不在监视列表中则返回 `ENOENT` 错误。
+    pub fn enable(&mut self, fd: i32) -> Result<(), SysError> {
+        let events = match self.watches.get(fd) {
+            Some(watch) => watch.events,
+            None => return Err(SysError::from(libc::ENOENT)),
+        };
+        self.ctl(libc::EPOLL_CTL_MOD, fd, events)?;
+        self.watches.get_mut(fd).unwrap().disabled = false;
+        Ok(())
+    }
+
+    /// 检查 `fd` 是否已被 `disable` 暂停，`fd` 不在监视列表中时返回 `false`。
+    pub fn is_disabled(&self, fd: i32) -> bool {
+        self.watches.get(fd).is_some_and(|watch| watch.disabled)
+    }
+
+    /// 使用已保存的事件集合重新启用一个单次触发的文件描述符。
+    ///
+    /// `fd` 已被 `disable` 暂停时不做任何操作，`enable` 会以保存的事件集合重新启用它。
+    /// 如果 `fd` 不在监视列表中则返回 `ENOENT` 错误。
+    pub fn rearm(&mut self, fd: i32) -> Result<(), SysError> {
+        match self.watches.get(fd) {
+            Some(watch) if watch.disabled => Ok(()),
+            Some(watch) => self.ctl(libc::EPOLL_CTL_MOD, fd, watch.events),
+            None => Err(SysError::from(libc::ENOENT)),
+        }
+    }
+
+    /// 将一个文件描述符从监视列表中移除。
+    ///
+    /// 通过 `add_owned` 添加的 `fd` 会在移除后被关闭。返回 `fd` 关联的上下文（如果有），
+    /// 以便调用者执行清理工作。
+    ///
+    /// 如果 `fd` 不在监视列表中则返回 `ENOENT` 错误。`fd` 已被其它线程关闭时内核会返回
+    /// `EBADF` 或 `ENOENT`，此时 `fd` 同样已不再被监视，因此仍会移除监视项并正常返回。
+    pub fn remove(&mut self, fd: i32) -> Result<Option<C>, SysError> {
+        if !self.watches.contains_key(fd) {
+            return Err(SysError::from(libc::ENOENT));
+        }
+        self.ctl_del(fd)?;
+        Ok(self.watches.remove(fd).unwrap().ctx)
+    }
+
+    /// 使监视列表与 `desired` 一致，只对发生变化的 `fd` 调用 `epoll_ctl`。
+    ///
+    /// 不在 `desired` 中的 `fd` 会被移除（`DEL`），新增的 `fd` 会被添加（`ADD`），
+    /// 事件集合发生变化的 `fd` 会通过 `modify` 修改（`MOD`），因此同样会拒绝独占唤醒标志并保留边沿触发模式；
+    /// 事件集合相同的 `fd` 只更新上下文而不调用 `epoll_ctl`。
+    /// 整个过程并非原子操作：个别 `fd` 失败时会继续处理其余的 `fd`，最后返回遇到的第一个错误。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, FdMap, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().write(), None).unwrap();
+    /// let mut desired = FdMap::new();
+    /// desired.insert(2, (Events::new().write(), None));
+    /// poller.reconcile(&desired).unwrap();
+    /// assert!(!poller.contains(1));
+    /// assert!(poller.contains(2));
+    /// ```
+    pub fn reconcile(&mut self, desired: &FdMap<(Events, Option<C>)>) -> Result<(), SysError>
+    where
+        C: Clone,
+    {
+        let mut result = Ok(());
+        let mut keep_first = |r: Result<(), SysError>| {
+            if let Err(err) = r {
+                if result.is_ok() {
+                    result = Err(err);
+                }
+            }
+        };
+        let departed: Vec<i32> = self
+            .watches
+            .iter()
+            .map(|(fd, _)| fd)
+            .filter(|fd| !desired.contains_key(fd))
+            .collect();
+        for fd in departed {
+            keep_first(self.remove(fd).map(|_| ()));
+        }
+        for (&fd, (events, ctx)) in desired.iter() {
+            let changed = match self.watches.get(fd) {
+                // 与 `modify` 相同，已处于边沿触发模式的 `fd` 会保留该模式。
+                Some(watch) if watch.events.has_edge_triggered() => {
+                    watch.events != events.edge_triggered()
+                }
+                Some(watch) => watch.events != *events,
+                None => {
+                    keep_first(self.add(fd, *events, ctx.clone()));
+                    continue;
+                }
+            };
+            if changed {
+                keep_first(self.modify(fd, *events, ctx.clone()));
+            } else {
+                self.watches.get_mut(fd).unwrap().ctx = ctx.clone();
+            }
+        }
+        result
+    }
+
+    /// 将一个通过 `add_source` 添加的对象从监视列表中移除，与 `remove(src.as_raw_fd())` 相同。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add_source(&std::io::stdout(), Events::new().write(), None).unwrap();
+    /// poller.remove_source(&std::io::stdout()).unwrap();
+    /// assert!(poller.is_empty());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn remove_source<T: AsRawFd>(&mut self, src: &T) -> Result<Option<C>, SysError> {
+        self.remove(src.as_raw_fd())
+    }
+
+    /// 将所有文件描述符从监视列表中移除。
+    ///
+    /// 与 `remove` 相同，已被关闭的 `fd`（`EBADF`、`ENOENT`）视为移除成功。
+    /// 即使个别 `fd` 移除失败也会继续处理剩余的 `fd` 并清空监视列表，最后返回遇到的第一个错误。
+    pub fn clear(&mut self) -> Result<(), SysError> {
+        let mut result = Ok(());
+        for (fd, _) in self.watches.iter() {
+            if let Err(err) = self.ctl_del(fd) {
+                if result.is_ok() {
+                    result = Err(err);
+                }
+            }
+        }
+        self.watches.clear();
+        result
+    }
+
+    /// 移除监视列表中已被关闭的 `fd`，返回被移除的数量。
+    ///
+    /// 调用者关闭 `fd` 而没有调用 `remove` 时，内核会自动将其从 epoll 中移除，但监视列表中仍保留着
+    /// 过期的条目及上下文。此函数通过 `fcntl(F_GETFD)` 检查每个 `fd`，移除返回 `EBADF` 的条目。
+    ///
+    /// **注意：** 如果 `fd` 编号已被重新分配给其它文件，则无法检测到。
+    pub fn prune(&mut self) -> usize {
+        let dead: Vec<i32> = self
+            .watches
+            .iter()
+            .map(|(fd, _)| fd)
+            .filter(|&fd| {
+                let ret = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+                ret < 0 && SysError::last().code() == libc::EBADF
+            })
+            .collect();
+        for &fd in dead.iter() {
+            #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+            let watch = self.watches.remove(fd);
+            // `fd` 已被关闭，不能再次关闭。
+            #[cfg(feature = "std")]
+            if let Some(owned) = watch.and_then(|mut watch| watch.owned.take()) {
+                let _ = owned.into_raw_fd();
+            }
+        }
+        dead.len()
+    }
+
+    /// 唤醒正在阻塞等待事件的线程，使 `pull_events` 立即返回。
+    ///
+    /// 可以在其它线程中调用；唤醒事件会在 `pull_events` 内部被消耗掉，不会出现在返回结果中。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Poller;
+    /// use std::sync::Arc;
+    /// let poller = Arc::new(Poller::new().unwrap());
+    /// let waker = Arc::clone(&poller);
+    /// std::thread::spawn(move || waker.wake().unwrap());
+    /// assert!(poller.pull_events(-1).unwrap().is_empty());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn wake(&self) -> Result<(), SysError> {
+        let waker = self.waker.as_ref().ok_or(SysError::from(libc::EBADF))?;
+        match waker.write(1) {
+            // 计数器溢出时返回 `EAGAIN`，此时已经有未处理的唤醒，可以忽略。
+            Err(err) if err.code() == libc::EAGAIN => Ok(()),
+            result => result,
+        }
+    }
+
+    /// 返回一个在 `fd` 可读时完成的 `Future`，结果为触发的事件集合。
+    ///
+    /// `fd` 不能已在监视列表中，否则返回 `EEXIST` 错误；该 `Future` 需要由 `pull_events` 驱动。
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use poller::Poller;
+    /// use std::sync::Arc;
+    /// let poller = Arc::new(Poller::new().unwrap());
+    /// let reactor = Arc::clone(&poller);
+    /// std::thread::spawn(move || loop {
+    ///     reactor.pull_events(-1).unwrap();
+    /// });
+    /// let readable = poller.readable(0);
+    /// // 在任意异步运行时中：`let events = readable.await?;`
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn readable(&self, fd: i32) -> Readable<'_, C> {
+        Readable {
+            poller: self,
+            fd,
+            registered: false,
+        }
+    }
+
+    /// 返回自创建以来累计的统计数据。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Poller;
+    /// let poller = Poller::new().unwrap();
+    /// poller.pull_events(0).unwrap();
+    /// assert_eq!(poller.stats().timeouts, 1);
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> PollerStats {
+        PollerStats {
+            waits: self.stats.waits.load(Ordering::Relaxed),
+            events: self.stats.events.load(Ordering::Relaxed),
+            timeouts: self.stats.timeouts.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 检查 `fd` 是否在监视列表中。
+    pub fn contains(&self, fd: i32) -> bool {
+        self.watches.contains_key(fd)
+    }
+
+    /// 读取并清除套接字 `fd` 上待处理的错误（`SO_ERROR`），没有错误时返回 `None`。
+    ///
+    /// 收到 `Events::has_error()` 事件后可以通过此函数取得具体的错误码，
+    /// 例如非阻塞 `connect` 失败时的 `ECONNREFUSED`；`fd` 不是套接字时返回 `ENOTSOCK` 错误。
+    pub fn take_error(&self, fd: i32) -> Result<Option<SysError>, SysError> {
+        let mut err: libc::c_int = 0;
+        let mut len = core::mem::size_of::<libc::c_int>() as libc::socklen_t;
+        let ret = unsafe {
+            libc::getsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_ERROR,
+                &mut err as *mut libc::c_int as *mut libc::c_void,
+                &mut len,
+            )
+        };
+        if ret < 0 {
+            Err(SysError::last().with_context("getsockopt SO_ERROR"))
+        } else if err == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(SysError::from(err)))
+        }
+    }
+
+    /// 返回 `fd` 当前注册的事件集合，`fd` 不在监视列表中时返回 `None`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().read(), None).unwrap();
+    /// let events = poller.events_for(1).unwrap() | Events::new().write();
+    /// if poller.events_for(1) != Some(events) {
+    ///     poller.modify(1, events, None).unwrap();
+    /// }
+    /// assert_eq!(poller.events_for(1), Some(Events::new().read().write()));
+    /// ```
+    pub fn events_for(&self, fd: i32) -> Option<Events> {
+        self.watches.get(fd).map(|watch| watch.events)
+    }
+
+    /// 返回 `fd` 关联的上下文，`fd` 不在监视列表中或没有上下文时返回 `None`。
+    pub fn get_context(&self, fd: i32) -> Option<&C> {
+        self.watches.get(fd).and_then(|watch| watch.ctx.as_ref())
+    }
+
+    /// 返回 `fd` 注册时指定的令牌，`fd` 不在监视列表中时返回 `None`。
+    pub fn token(&self, fd: i32) -> Option<u64> {
+        self.watches.get(fd).map(|watch| watch.token)
+    }
+
+    /// 替换 `fd` 关联的上下文，仅修改监视列表而不会调用 `epoll_ctl`。
+    ///
+    /// 如果 `fd` 不在监视列表中则返回 `ENOENT` 错误。
+    pub fn set_context(&mut self, fd: i32, ctx: Option<C>) -> Result<(), SysError> {
+        match self.watches.get_mut(fd) {
+            Some(watch) => {
+                watch.ctx = ctx;
+                Ok(())
+            }
+            None => Err(SysError::from(libc::ENOENT)),
+        }
+    }
+
+    /// 返回监视列表中 `fd` 的数量。
+    pub fn len(&self) -> usize {
+        self.watches.len()
+    }
+
+    /// 检查监视列表是否为空。
+    pub fn is_empty(&self) -> bool {
+        self.watches.is_empty()
+    }
+
+    /// 返回一个遍历监视列表中所有 `fd` 及其事件集合的迭代器，顺序不固定。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().write(), None).unwrap();
+    /// for (fd, events) in poller.watches() {
+    ///     println!("Fd={}, Events={}", fd, events);
+    /// }
+    /// ```
+    pub fn watches(&self) -> impl Iterator<Item = (i32, Events)> + '_ {
+        self.watches.iter().map(|(fd, watch)| (fd, watch.events))
+    }
+
+    /// 对 `fd` 执行 `epoll_ctl` 的添加或修改操作。
+    ///
+    /// `fd` 被保存在 `epoll_event.u64` 中，`translate` 据此直接索引监视列表，令牌则保存在监视项内。
+    fn ctl(&self, op: i32, fd: i32, events: Events) -> Result<(), SysError> {
+        let mut ev = libc::epoll_event {
+            events: events.into(),
+            u64: fd as u64,
+        };
+        let err = unsafe { epoll_ctl(self.epoll_fd, op, fd, &mut ev) };
+        if err < 0 {
+            let context = match op {
+                libc::EPOLL_CTL_ADD => "epoll_ctl ADD",
+                libc::EPOLL_CTL_MOD => "epoll_ctl MOD",
+                libc::EPOLL_CTL_DEL => "epoll_ctl DEL",
+                _ => "epoll_ctl",
+            };
+            Err(SysError::last().with_context(context))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 对 `fd` 执行 `epoll_ctl` 的删除操作，`fd` 已被关闭（`EBADF`、`ENOENT`）时视为成功。
+    fn ctl_del(&self, fd: i32) -> Result<(), SysError> {
+        let err = unsafe {
+            epoll_ctl(
+                self.epoll_fd,
+                libc::EPOLL_CTL_DEL,
+                fd,
+                core::ptr::null_mut(),
+            )
+        };
+        if err < 0 {
+            let err = SysError::last();
+            if err.code() != libc::EBADF && err.code() != libc::ENOENT {
+                return Err(err.with_context("epoll_ctl DEL"));
+            }
+        }
+        Ok(())
+    }
+
+    /// 拉取所有被监测到的 I/O 事件。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().write(), None).unwrap();
+    /// for (fd, events, ctx) in poller.pull_events(1000).unwrap() {
+    ///     println!("Fd={}, Events={}, Context={:?}", fd, events, ctx);
+    /// }
+    /// ```
+    pub fn pull_events(&self, timeout_ms: i32) -> Result<Ready<'_, C>, SysError> {
+        let mut events = Vec::new();
+        self.wait(&mut events, ms_to_timeout(timeout_ms))?;
+        Ok(Ready { events })
+    }
+
+    /// 拉取所有被监测到的 I/O 事件，等待期间定期检查 `cancel` 标志。
+    ///
+    /// 整个等待被拆分为多次不超过 50 毫秒的短等待，每次等待前检查 `cancel`，
+    /// 被设置时返回 `CancellableStatus::Cancelled`；超时返回 `CancellableStatus::Timeout`，
+    /// 被 `wake` 唤醒时返回空的 `CancellableStatus::Events`。
+    /// 使用 `wake` 唤醒更加高效，此函数便于接入已经使用 `AtomicBool` 作为退出标志的代码。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{CancellableStatus, Poller};
+    /// use std::sync::atomic::AtomicBool;
+    /// let poller = Poller::new().unwrap();
+    /// let cancel = AtomicBool::new(true);
+    /// let status = poller.pull_events_cancellable(-1, &cancel).unwrap();
+    /// assert!(matches!(status, CancellableStatus::Cancelled));
+    /// ```
+    pub fn pull_events_cancellable(
+        &self,
+        timeout_ms: i32,
+        cancel: &AtomicBool,
+    ) -> Result<CancellableStatus<'_, C>, SysError> {
+        let timeout = ms_to_timeout(timeout_ms);
+        let start = monotonic_now();
+        let mut events = Vec::new();
+        loop {
+            if cancel.load(Ordering::Acquire) {
+                return Ok(CancellableStatus::Cancelled);
+            }
+            let elapsed = monotonic_now() - start;
+            let remaining = timeout.map(|d| d.checked_sub(elapsed).unwrap_or_default());
+            let slice = remaining.map_or(CANCEL_CHECK_INTERVAL, |d| d.min(CANCEL_CHECK_INTERVAL));
+            if self.wait(&mut events, Some(slice))? > 0 {
+                return Ok(CancellableStatus::Events(Ready { events }));
+            }
+            if remaining == Some(slice) {
+                return Ok(CancellableStatus::Timeout);
+            }
+        }
+    }
+
+    /// 以非阻塞方式拉取已经就绪的 I/O 事件，没有就绪的事件时立即返回空列表。
+    ///
+    /// 等同于 `pull_events(0)`；需要避免每次分配内存时可以使用 `pull_events_into(&mut buf, 0)`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Poller;
+    /// let poller = Poller::new().unwrap();
+    /// assert!(poller.try_pull_events().unwrap().is_empty());
+    /// ```
+    pub fn try_pull_events(&self) -> Result<Ready<'_, C>, SysError> {
+        self.pull_events(0)
+    }
+
+    /// 拉取所有被监测到的 I/O 事件，超时时间以 `Duration` 表示。
+    ///
+    /// `None` 表示无限等待，`Some(d)` 会向上取整到毫秒并饱和截断到 `i32::MAX` 毫秒。
+    ///
+    /// 等待过程被信号中断（`EINTR`）时会以剩余的超时时间自动重试。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// use std::time::Duration;
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().write(), None).unwrap();
+    /// let events = poller.pull_events_timeout(Some(Duration::from_secs(1))).unwrap();
+    /// assert_eq!(events.len(), 1);
+    /// ```
+    pub fn pull_events_timeout(&self, timeout: Option<Duration>) -> Result<Ready<'_, C>, SysError> {
+        let mut events = Vec::new();
+        self.wait(&mut events, timeout)?;
+        Ok(Ready { events })
+    }
+
+    /// 返回一个持续拉取事件的阻塞迭代器，每次等待的超时时间为 `timeout_ms` 毫秒。
+    ///
+    /// 迭代器只会在 `pull_events` 出错时结束，错误可通过 `EventIter::last_error` 取得。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().write(), None).unwrap();
+    /// for (fd, events, _ctx) in poller.events(1000).take(3) {
+    ///     assert_eq!((fd, events), (1, Events::new().write()));
+    /// }
+    /// ```
+    pub fn events(&self, timeout_ms: i32) -> EventIter<'_, C> {
+        EventIter {
+            poller: self,
+            timeout_ms,
+            batch: Vec::new().into_iter(),
+            error: None,
+        }
+    }
+
+    /// 拉取所有被监测到的 I/O 事件，并附带每个 `fd` 注册时指定的令牌。
+    ///
+    /// 内核返回的事件数据（`epoll_event.u64`）中保存的是 `fd`，令牌取自 `fd` 对应的监视项。
+    pub fn pull_events_with_tokens(
+        &self,
+        timeout_ms: i32,
+    ) -> Result<Vec<TokenEventData<'_, C>>, SysError> {
+        let mut events = Vec::new();
+        self.wait(&mut events, ms_to_timeout(timeout_ms))?;
+        Ok(events)
+    }
+
+    /// 拉取所有被监测到的 I/O 事件，最多等待到 `deadline` 时刻。
+    ///
+    /// `deadline` 已经过去时不会等待，直接返回空列表。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Poller;
+    /// use std::time::{Duration, Instant};
+    /// let poller = Poller::new().unwrap();
+    /// let deadline = Instant::now() + Duration::from_millis(10);
+    /// assert!(poller.pull_events_until(deadline).unwrap().is_empty());
+    /// assert!(Instant::now() >= deadline);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn pull_events_until(&self, deadline: Instant) -> Result<Ready<'_, C>, SysError> {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining == Duration::from_secs(0) {
+            return Ok(Ready { events: Vec::new() });
+        }
+        self.pull_events_timeout(Some(remaining))
+    }
+
+    /// 拉取所有被监测到的 I/O 事件，超时时间精确到纳秒。
+    ///
+    /// 内核支持时使用 `epoll_pwait2`，否则退回到 `epoll_wait` 并将超时时间向上取整到毫秒；
+    /// 探测结果会被缓存。等待过程被信号中断（`EINTR`）时会以剩余的超时时间自动重试。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Poller;
+    /// use std::time::Duration;
+    /// let poller = Poller::new().unwrap();
+    /// let events = poller.pull_events_deadline(Duration::from_micros(100)).unwrap();
+    /// assert!(events.is_empty());
+    /// ```
+    pub fn pull_events_deadline(&self, timeout: Duration) -> Result<Ready<'_, C>, SysError> {
+        let mut events = Vec::new();
+        let start = monotonic_now();
+        loop {
+            let remaining = timeout
+                .checked_sub(monotonic_now() - start)
+                .unwrap_or_default();
+            match self.wait_pwait2(&mut events, remaining) {
+                Err(err) if err.is_interrupted() => continue,
+                Err(err) => return Err(err),
+                Ok(_) => return Ok(Ready { events }),
+            }
+        }
+    }
+
+    /// 拉取所有被监测到的 I/O 事件，只调用一次 `epoll_wait`。
+    ///
+    /// 与 `pull_events` 不同，等待过程被信号中断时直接返回 `EINTR` 错误而不会重试。
+    pub fn pull_events_once(&self, timeout_ms: i32) -> Result<Ready<'_, C>, SysError> {
+        let mut events = Vec::new();
+        self.wait_once(&mut events, timeout_ms)?;
+        Ok(Ready { events })
+    }
+
+    /// 以 `sigmask` 作为等待期间的信号掩码拉取所有被监测到的 I/O 事件（`epoll_pwait`）。
+    ///
+    /// 替换信号掩码与等待是原子完成的，平时阻塞的信号只会在等待期间被递送，
+    /// 此时返回 `EINTR` 错误而不会重试，以便调用者及时处理信号。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().write(), None).unwrap();
+    /// let mut sigmask = unsafe { core::mem::zeroed() };
+    /// unsafe { libc::sigemptyset(&mut sigmask) };
+    /// assert_eq!(poller.pull_events_pwait(1000, &sigmask).unwrap().len(), 1);
+    /// ```
+    pub fn pull_events_pwait(
+        &self,
+        timeout_ms: i32,
+        sigmask: &libc::sigset_t,
+    ) -> Result<Ready<'_, C>, SysError> {
+        let mut events = Vec::new();
+        self.wait_with(
+            &mut events,
+            "epoll_pwait",
+            timeout_ms,
+            |ev, maxevents| unsafe {
+                libc::epoll_pwait(self.epoll_fd, ev, maxevents, timeout_ms, sigmask)
+            },
+        )?;
+        Ok(Ready { events })
+    }
+
+    /// 等待事件并一次性取出当前所有已就绪的事件，最多返回 `max_total` 个。
+    ///
+    /// 首次等待最多 `timeout_ms` 毫秒，之后以 `0` 超时反复调用 `epoll_wait`，
+    /// 直到不再有新的 `fd` 就绪或达到 `max_total` 为止。
+    /// 水平触发的 `fd` 在每次调用中都会被重复报告，同一个 `fd` 在结果中只保留第一次出现的事件。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap().with_max_events(1);
+    /// poller.add(1, Events::new().write(), None).unwrap();
+    /// poller.add(2, Events::new().write(), None).unwrap();
+    /// let fds: Vec<i32> = poller.drain(1000, 16).unwrap().ready_events().map(|e| e.fd).collect();
+    /// assert_eq!(fds.len(), 2);
+    /// ```
+    pub fn drain(&self, timeout_ms: i32, max_total: usize) -> Result<Ready<'_, C>, SysError> {
+        let mut events = Vec::new();
+        let mut seen = Set::new();
+        let mut batch: Vec<EventData<'_, C>> = Vec::new();
+        self.wait(&mut batch, ms_to_timeout(timeout_ms))?;
+        loop {
+            let mut added = false;
+            for data in batch.drain(..) {
+                if events.len() >= max_total {
+                    return Ok(Ready { events });
+                }
+                if seen.insert(data.0) {
+                    events.push(data);
+                    added = true;
+                }
+            }
+            if !added || events.len() >= max_total {
+                return Ok(Ready { events });
+            }
+            self.wait(&mut batch, Some(Duration::from_secs(0)))?;
+        }
+    }
+
+    /// 拉取所有被监测到的 I/O 事件到调用者提供的缓冲区中，返回事件的数量。
+    ///
+    /// 缓冲区会先被清空再填充，可在事件循环中重复使用以避免每次调用都分配内存。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().write(), None).unwrap();
+    /// let mut events = Vec::new();
+    /// for _ in 0..3 {
+    ///     assert_eq!(poller.pull_events_into(&mut events, 1000).unwrap(), 1);
+    /// }
+    /// ```
+    pub fn pull_events_into<'a>(
+        &'a self,
+        buf: &mut Vec<EventData<'a, C>>,
+        timeout_ms: i32,
+    ) -> Result<usize, SysError> {
+        self.wait(buf, ms_to_timeout(timeout_ms))?;
+        Ok(buf.len())
+    }
+
+    /// 拉取所有被监测到的 I/O 事件，并区分等待超时与没有就绪 `fd` 的唤醒。
+    ///
+    /// 只有在超时时间内内核没有报告任何事件时才返回 `WaitStatus::Timeout`；
+    /// 被 `wake` 唤醒或仅有内部事件时返回空的 `WaitStatus::Events`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Poller, WaitStatus};
+    /// let poller = Poller::new().unwrap();
+    /// assert!(matches!(poller.pull_events_status(10).unwrap(), WaitStatus::Timeout));
+    /// ```
+    pub fn pull_events_status(&self, timeout_ms: i32) -> Result<WaitStatus<'_, C>, SysError> {
+        let mut events = Vec::new();
+        let nfds = self.wait(&mut events, ms_to_timeout(timeout_ms))?;
+        if nfds == 0 {
+            Ok(WaitStatus::Timeout)
+        } else {
+            Ok(WaitStatus::Events(Ready { events }))
+        }
+    }
+
+    /// 拉取 I/O 事件到调用者提供的定长数组中，返回填充的事件数量。
+    ///
+    /// 每次最多返回 `N` 个事件，整个过程不会分配堆内存，适合在栈上分配缓冲区的嵌入式场景。
+    /// 等待过程被信号中断（`EINTR`）时会以剩余的超时时间自动重试。
+    /// `N` 为 0 时内核会拒绝 `maxevents` 为 0 的请求，因此不等待直接返回 `Ok(0)`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().write(), None).unwrap();
+    /// let mut events = [(0, Events::new(), None); 8];
+    /// let n = poller.pull_events_array(&mut events, 1000).unwrap();
+    /// assert_eq!(n, 1);
+    /// assert_eq!(events[0].0, 1);
+    /// ```
+    pub fn pull_events_array<'a, const N: usize>(
+        &'a self,
+        buf: &mut [EventData<'a, C>; N],
+        timeout_ms: i32,
+    ) -> Result<usize, SysError> {
+        if N == 0 {
+            return Ok(0);
+        }
+        let mut ev = [libc::epoll_event { events: 0, u64: 0 }; N];
+        retry_interrupted(ms_to_timeout(timeout_ms), |timeout_ms| {
+            let nfds = traced_wait("epoll_wait", timeout_ms, || unsafe {
+                epoll_wait(self.epoll_fd, ev.as_mut_ptr(), N as i32, timeout_ms)
+            })?;
+            let mut n = 0;
+            self.translate(&ev[..nfds], |data| {
+                buf[n] = data;
+                n += 1;
+            });
+            #[cfg(feature = "stats")]
+            self.record(nfds, n);
+            Ok(n)
+        })
+    }
+
+    /// 等待并返回一个就绪的事件，超时或被 `wake` 唤醒时返回 `None`。
+    ///
+    /// 每次只向内核请求一个事件（`maxevents = 1`），其余就绪的事件留给后续调用，
+    /// 适合严格按顺序逐个处理事件的状态机。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().write(), None).unwrap();
+    /// let (fd, events, _ctx) = poller.wait_one(1000).unwrap().unwrap();
+    /// assert_eq!((fd, events), (1, Events::new().write()));
+    /// ```
+    pub fn wait_one(&self, timeout_ms: i32) -> Result<Option<EventData<'_, C>>, SysError> {
+        let mut buf = [(0, Events::new(), None); 1];
+        let n = self.pull_events_array(&mut buf, timeout_ms)?;
+        Ok(if n == 0 { None } else { Some(buf[0]) })
+    }
+
+    /// 通过一次 `poll(2)` 查询 `fd` 当前的就绪状态，返回 `interest` 中已就绪的事件及错误、挂起事件。
+    ///
+    /// 查询与 epoll 的监视列表无关，`fd` 不需要已被添加，也不会影响已注册的事件；
+    /// 最多等待 `timeout_ms` 毫秒，被信号中断时自动重试。`fd` 无效时返回 `EBADF` 错误。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, Poller};
+    /// let poller = Poller::new().unwrap();
+    /// let ready = poller.check(1, Events::new().write(), 0).unwrap();
+    /// assert!(ready.has_write());
+    /// ```
+    pub fn check(&self, fd: i32, interest: Events, timeout_ms: i32) -> Result<Events, SysError> {
+        let mut pfd = libc::pollfd {
+            fd,
+            events: 0,
+            revents: 0,
+        };
+        let flags = [
+            (Events::new().read(), libc::POLLIN),
+            (Events::new().write(), libc::POLLOUT),
+            (Events::new().priority(), libc::POLLPRI),
+            (Events::new().read_hangup(), libc::POLLRDHUP),
+            (Events::new().error(), libc::POLLERR),
+            (Events::new().with(Event::HangUp), libc::POLLHUP),
+        ];
+        for (events, flag) in flags.iter() {
+            if interest.contains(*events) {
+                pfd.events |= flag;
+            }
+        }
+        retry_interrupted(ms_to_timeout(timeout_ms), |timeout_ms| {
+            let n = unsafe { libc::poll(&mut pfd, 1, timeout_ms) };
+            if n < 0 {
+                Err(SysError::last().with_context("poll"))
+            } else {
+                Ok(n as usize)
+            }
+        })?;
+        if pfd.revents & libc::POLLNVAL != 0 {
+            return Err(SysError::from(libc::EBADF));
+        }
+        let mut ready = Events::new();
+        for (events, flag) in flags.iter() {
+            if pfd.revents & flag != 0 {
+                ready = ready.union(*events);
+            }
+        }
+        Ok(ready)
+    }
+
+    /// 运行事件循环，把每个就绪的 `fd` 分发给 `handlers` 中对应的处理器。
+    ///
+    /// 每次等待的超时时间为 `timeout_ms` 毫秒，超时后继续等待；没有对应处理器的 `fd` 会被忽略。
+    /// 任意处理器返回 `false` 时，处理完当前这批事件后停止循环并返回 `Ok(())`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Events, FdMap, Poller};
+    /// let mut poller = Poller::new().unwrap();
+    /// poller.add(1, Events::new().write(), None).unwrap();
+    /// let mut handlers: FdMap<Box<dyn FnMut(i32, Events) -> bool>> = FdMap::new();
+    /// handlers.insert(1, Box::new(|_fd, events| !events.has_write()));
+    /// poller.run(&mut handlers, 1000).unwrap();
+    /// ```
+    pub fn run<H: EventHandler>(
+        &mut self,
+        handlers: &mut FdMap<H>,
+        timeout_ms: i32,
+    ) -> Result<(), SysError> {
+        let mut buf = Vec::new();
+        loop {
+            self.pull_events_into(&mut buf, timeout_ms)?;
+            let mut running = true;
+            for (fd, events, _) in buf.iter() {
+                if let Some(handler) = handlers.get_mut(fd) {
+                    running &= handler.handle(*fd, *events);
+                }
+            }
+            if !running {
+                return Ok(());
+            }
+        }
+    }
+
+    /// 运行事件循环，把每个事件交给 `on_event`，每次等待超时时调用 `on_idle`。
+    ///
+    /// 每次等待的超时时间为 `timeout_ms` 毫秒，只有期间内核没有报告任何事件时才算超时，
+    /// 适合在处理请求的同时定期执行维护工作。`on_idle` 返回 `false` 时立即停止循环；
+    /// `on_event` 返回 `false` 时，处理完当前这批事件后停止循环。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Poller;
+    /// let mut poller = Poller::new().unwrap();
+    /// let mut ticks = 0;
+    /// poller
+    ///     .run_until(10, || { ticks += 1; ticks < 3 }, |_event| true)
+    ///     .unwrap();
+    /// assert_eq!(ticks, 3);
+    /// ```
+    pub fn run_until<I, E>(
+        &mut self,
+        timeout_ms: i32,
+        mut on_idle: I,
+        mut on_event: E,
+    ) -> Result<(), SysError>
+    where
+        I: FnMut() -> bool,
+        E: FnMut(EventData<'_, C>) -> bool,
+    {
+        let mut buf = Vec::new();
+        loop {
+            if self.wait(&mut buf, ms_to_timeout(timeout_ms))? == 0 {
+                if !on_idle() {
+                    return Ok(());
+                }
+                continue;
+            }
+            let mut running = true;
+            for data in buf.drain(..) {
+                running &= on_event(data);
+            }
+            if !running {
+                return Ok(());
+            }
+        }
+    }
+
+    /// 等待 I/O 事件，被信号中断时以剩余的超时时间自动重试。
+    fn wait<'a, T: EventRecord<'a, C>>(
+        &'a self,
+        buf: &mut Vec<T>,
+        timeout: Option<Duration>,
+    ) -> Result<usize, SysError> {
+        retry_interrupted(timeout, |timeout_ms| self.wait_once(buf, timeout_ms))
+    }
+
+    /// 调用一次 `epoll_pwait2` 并将结果填充到 `buf` 中，内核不支持时退回到 `epoll_wait`。
+    fn wait_pwait2<'a, T: EventRecord<'a, C>>(
+        &'a self,
+        buf: &mut Vec<T>,
+        timeout: Duration,
+    ) -> Result<usize, SysError> {
+        if !PWAIT2_UNSUPPORTED.load(Ordering::Relaxed) {
+            let ts = crate::to_timespec(timeout);
+            let timeout_ms = timeout_to_ms(Some(timeout));
+            let result = self.wait_with(buf, "epoll_pwait2", timeout_ms, |ev, maxevents| unsafe {
+                libc::syscall(
+                    libc::SYS_epoll_pwait2,
+                    self.epoll_fd,
+                    ev,
+                    maxevents,
+                    &ts as *const libc::timespec,
+                    core::ptr::null::<libc::sigset_t>(),
+                    0usize,
+                ) as i32
+            });
+            match result {
+                Err(err) if err.code() == libc::ENOSYS => {
+                    PWAIT2_UNSUPPORTED.store(true, Ordering::Relaxed)
+                }
+                result => return result,
+            }
+        }
+        self.wait_once(buf, timeout_to_ms(Some(timeout)))
+    }
+
+    /// 累计一次等待的统计数据。
+    #[cfg(feature = "stats")]
+    fn record(&self, nfds: usize, events: usize) {
+        self.stats.waits.fetch_add(1, Ordering::Relaxed);
+        self.stats
+            .events
+            .fetch_add(events as u64, Ordering::Relaxed);
+        if nfds == 0 {
+            self.stats.timeouts.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 调用一次 `epoll_wait` 并将结果填充到 `buf` 中。
+    fn wait_once<'a, T: EventRecord<'a, C>>(
+        &'a self,
+        buf: &mut Vec<T>,
+        timeout_ms: i32,
+    ) -> Result<usize, SysError> {
+        self.wait_with(buf, "epoll_wait", timeout_ms, |ev, maxevents| unsafe {
+            epoll_wait(self.epoll_fd, ev, maxevents, timeout_ms)
+        })
+    }
+
+    /// 调用一次 `wait` 指定的等待函数并将结果填充到 `buf` 中。
+    ///
+    /// 等待函数的参数为事件缓冲区及 `maxevents`，返回值与 `epoll_wait` 相同；出错时附加 `context`。
+    /// 返回内核报告的事件数量（包含不会填充到 `buf` 中的唤醒事件），为 0 表示等待超时。
+    /// 除 `pull_events_array` 外的 `pull_events` 系列方法都经由此处等待。
+    fn wait_with<'a, T, F>(
+        &'a self,
+        buf: &mut Vec<T>,
+        context: &'static str,
+        timeout_ms: i32,
+        wait: F,
+    ) -> Result<usize, SysError>
+    where
+        T: EventRecord<'a, C>,
+        F: FnOnce(*mut libc::epoll_event, i32) -> i32,
+    {
+        buf.clear();
+        // 额外为唤醒事件保留一个位置，同时保证 `maxevents` 不为 0。
+        let maxevents = (self.watches.len() + self.readers_len() + 1).min(self.max_events);
+        self.with_scratch(|ev| {
+            ev.clear();
+            ev.reserve(maxevents);
+            let nfds = traced_wait(context, timeout_ms, || {
+                wait(ev.as_mut_ptr(), maxevents as i32)
+            })?;
+            unsafe { ev.set_len(nfds) };
+            self.translate(ev, |data| buf.push(data));
+            #[cfg(feature = "stats")]
+            self.record(nfds, buf.len());
+            Ok(nfds)
+        })
+    }
+
+    /// 以内部的事件缓冲区调用 `f`，其它线程正在使用时退回到临时分配。
+    #[cfg(feature = "std")]
+    fn with_scratch<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Vec<libc::epoll_event>) -> R,
+    {
+        match self.scratch.try_lock() {
+            Ok(mut guard) => f(&mut guard),
+            Err(_) => f(&mut Vec::new()),
+        }
+    }
+
+    /// 以临时分配的事件缓冲区调用 `f`。
+    #[cfg(not(feature = "std"))]
+    fn with_scratch<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Vec<libc::epoll_event>) -> R,
+    {
+        f(&mut Vec::new())
+    }
+
+    /// 返回正在等待 `readable` 的 `fd` 数量。
+    #[cfg(feature = "std")]
+    fn readers_len(&self) -> usize {
+        self.readers.lock().unwrap().len()
+    }
+
+    /// 关闭 `std` 时没有 `readable`，总是返回 `0`。
+    #[cfg(not(feature = "std"))]
+    fn readers_len(&self) -> usize {
+        0
+    }
+
+    /// 将 `epoll_wait` 返回的事件转换为 `EventData` 或 `TokenEventData` 并逐个交给 `push`。
+    ///
+    /// 内核事件数据中保存的是 `fd`，直接以其为下标取出监视项中的令牌及上下文；不在监视列表中的注册
+    /// （例如 `readable`）以 `fd` 本身作为令牌。唤醒事件会被消耗掉，等待 `readable` 的 `fd`
+    /// 会唤醒对应的任务，它们都不会交给 `push`。
+    fn translate<'a, T, F>(&'a self, ev: &[libc::epoll_event], mut push: F)
+    where
+        T: EventRecord<'a, C>,
+        F: FnMut(T),
+    {
+        // 等待期间不能持有锁，否则其它线程无法注册 `readable`。
+        #[cfg(feature = "std")]
+        let mut readers = self.readers.lock().unwrap();
+        for x in ev.iter() {
+            let fd = x.u64 as i32;
+            #[cfg(feature = "std")]
+            if let Some(waker) = self.waker.as_ref().filter(|w| w.as_raw_fd() == fd) {
+                let _ = waker.read();
+                continue;
+            }
+            let events = Events::from(x.events);
+            if let Some(watch) = self.watches.get(fd) {
+                push(T::new(watch.token, fd, events, watch.ctx.as_ref()));
+                continue;
+            }
+            #[cfg(feature = "std")]
+            if let Some(reader) = readers.get_mut(&fd) {
+                reader.events = Some(events);
+                reader.waker.wake_by_ref();
+                continue;
+            }
+            push(T::new(fd as u64, fd, events, None));
+        }
+    }
+}
+
+/// 调用一次等待函数并返回内核报告的事件数量，出错时附加 `context`。
+///
+/// 所有 `pull_events` 系列方法都经由此处调用内核，启用 `tracing` 特性时在这里记录 `timeout_ms` 及 `nfds`。
+fn traced_wait<F>(
+    context: &'static str,
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))] timeout_ms: i32,
+    wait: F,
+) -> Result<usize, SysError>
+where
+    F: FnOnce() -> i32,
+{
+    #[cfg(feature = "tracing")]
+    let span = tracing::trace_span!(
+        "pull_events",
+        syscall = context,
+        timeout_ms,
+        nfds = tracing::field::Empty
+    );
+    #[cfg(feature = "tracing")]
+    let _entered = span.enter();
+    let nfds = wait();
+    if nfds < 0 {
+        return Err(SysError::last().with_context(context));
+    }
+    #[cfg(feature = "tracing")]
+    span.record("nfds", nfds);
+    Ok(nfds as usize)
+}
+
+/// 返回单调时钟（`CLOCK_MONOTONIC`）的当前读数，用于计算剩余的超时时间。
+fn monotonic_now() -> Duration {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) };
+    Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+}
+
+/// 以毫秒形式的剩余超时时间调用 `wait`，被信号中断时自动重试。
+fn retry_interrupted<F>(timeout: Option<Duration>, mut wait: F) -> Result<usize, SysError>
+where
+    F: FnMut(i32) -> Result<usize, SysError>,
+{
+    let start = monotonic_now();
+    loop {
+        let elapsed = monotonic_now() - start;
+        let remaining = timeout.map(|d| d.checked_sub(elapsed).unwrap_or_default());
+        match wait(timeout_to_ms(remaining)) {
+            Err(err) if err.is_interrupted() => continue,
+            result => return result,
+        }
+    }
+}
+
+/// 检查 `events` 至少包含一个读、写、紧急数据或对端关闭写入事件。
+///
+/// 挂起及错误事件无论是否注册都会被内核报告，只有它们或触发方式等标志的注册没有实际的关注事件。
+fn check_interest(events: Events) -> Result<(), SysError> {
+    let interest = Events::new().read().write().priority().read_hangup();
+    if !events.intersects(interest) {
+        Err(SysError::from(libc::EINVAL).with_context("empty interest mask"))
+    } else {
+        Ok(())
+    }
+}
+
+/// 将毫秒形式的超时转换为 `Duration`，负数表示无限等待。
+fn ms_to_timeout(timeout_ms: i32) -> Option<Duration> {
+    if timeout_ms < 0 {
+        None
+    } else {
+        Some(Duration::from_millis(timeout_ms as u64))
+    }
+}
+
+/// 将 `Duration` 形式的超时转换为 `epoll_wait` 所需的毫秒数。
+fn timeout_to_ms(timeout: Option<Duration>) -> i32 {
+    match timeout {
+        None => -1,
+        Some(d) => {
+            let ms = d.as_millis() + u128::from(d.subsec_nanos() % 1_000_000 != 0);
+            ms.min(i32::MAX as u128) as i32
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poller() {
+        unsafe {
+            let cstr = std::ffi::CString::new("/proc/uptime").unwrap();
+            let fd = libc::open(cstr.as_ptr(), libc::O_RDONLY);
+            let mut poller = Poller::new().unwrap();
+            assert!(!poller.contains(fd));
+            assert!(poller.is_empty());
+            assert!(poller.add(fd, Events::new().read(), None).is_ok());
+            assert!(poller.contains(fd));
+            assert_eq!(poller.len(), 1);
+            assert_eq!(poller.events_for(fd), Some(Events::new().read()));
+            assert_eq!(
+                poller.watches().collect::<Vec<_>>(),
+                vec![(fd, Events::new().read())]
+            );
+            for _ in 0..1000 {
+                assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            }
+            assert!(poller.remove(fd).is_ok());
+            assert!(!poller.contains(fd));
+            assert!(poller.is_empty());
+            assert_eq!(poller.events_for(fd), None);
+            let err = poller
+                .add(i32::MAX, Events::new().read(), None)
+                .unwrap_err();
+            assert_eq!(
+                (err.code(), err.context()),
+                (libc::EBADF, Some("epoll_ctl ADD"))
+            );
+            for _ in 0..1000 {
+                assert!(poller.add(fd, Events::new().read(), None).is_ok());
+                assert!(poller.remove(fd).is_ok());
+            }
+            libc::close(fd);
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let poller = Poller::new().unwrap();
+        assert!(poller.pull_events(10).unwrap().is_empty());
+        assert!(poller.try_pull_events().unwrap().is_empty());
+        let deadline = Instant::now() - Duration::from_secs(1);
+        assert!(poller.pull_events_until(deadline).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_max_events() {
+        unsafe {
+            let mut fds = [0; 6];
+            let mut poller = Poller::new().unwrap().with_max_events(2);
+            for pair in fds.chunks_mut(2) {
+                assert_eq!(libc::pipe(pair.as_mut_ptr()), 0);
+                assert!(poller.add(pair[1], Events::new().write(), None).is_ok());
+            }
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 2);
+            for fd in fds.iter() {
+                libc::close(*fd);
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_owned() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            let owned = OwnedFd::from_raw_fd(fds[0]);
+            assert!(poller.add_owned(owned, Events::new().read(), None).is_ok());
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), 1);
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            assert!(poller.modify(fds[0], Events::new().read(), None).is_ok());
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), 1);
+            assert!(poller.remove(fds[0]).is_ok());
+            // 读端已被关闭，写入会返回 EPIPE。
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), -1);
+            assert_eq!(SysError::last(), SysError::from(libc::EPIPE));
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_context() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            let ctx: EventContext = Arc::new(1u32);
+            assert_eq!(
+                poller.set_context(fds[1], Some(ctx.clone())),
+                Err(SysError::from(libc::ENOENT))
+            );
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            assert!(poller.get_context(fds[1]).is_none());
+            assert!(poller.set_context(fds[1], Some(ctx)).is_ok());
+            let ctx = poller.get_context(fds[1]).unwrap();
+            assert_eq!(ctx.downcast_ref::<u32>(), Some(&1));
+            assert!(poller.pull_events(1000).unwrap()[0].2.is_some());
+            let ctx = poller.remove(fds[1]).unwrap().unwrap();
+            assert_eq!(ctx.downcast_ref::<u32>(), Some(&1));
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            assert!(poller.remove(fds[1]).unwrap().is_none());
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_add_twice() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[1], Events::new().read(), None).is_ok());
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            assert_eq!(
+                poller.watches().collect::<Vec<_>>(),
+                vec![(fds[1], Events::new().write())]
+            );
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            // 内核中的注册已经不存在时，重新添加会回退为 `EPOLL_CTL_ADD`。
+            let mut ev = libc::epoll_event { events: 0, u64: 0 };
+            assert_eq!(
+                epoll_ctl(poller.as_raw_fd(), libc::EPOLL_CTL_DEL, fds[1], &mut ev),
+                0
+            );
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            // 重新添加与 `modify` 的规则相同：保留边沿触发模式，拒绝独占唤醒标志。
+            assert!(poller.set_edge_triggered(fds[1], true).is_ok());
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            assert_eq!(
+                poller.events_for(fds[1]),
+                Some(Events::new().write().edge_triggered())
+            );
+            assert_eq!(
+                poller.add(fds[1], Events::new().write().exclusive(), None),
+                Err(SysError::from(libc::EINVAL))
+            );
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_modify_or_add() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller
+                .add_with_token(fds[1], Events::new().read(), 7, None)
+                .is_ok());
+            assert!(poller
+                .modify_or_add(fds[1], Events::new().write(), None)
+                .is_ok());
+            assert_eq!(poller.token(fds[1]), Some(7));
+            assert!(poller
+                .modify_or_add(fds[0], Events::new().read(), None)
+                .is_ok());
+            assert_eq!(poller.len(), 2);
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_pull_events_array() {
+        unsafe {
+            let mut fds = [0; 6];
+            let mut poller = TypedPoller::<i32>::new().unwrap();
+            for pair in fds.chunks_mut(2) {
+                assert_eq!(libc::pipe(pair.as_mut_ptr()), 0);
+                assert!(poller
+                    .add(pair[1], Events::new().write(), Some(pair[1]))
+                    .is_ok());
+            }
+            let mut events = [(0, Events::new(), None); 2];
+            assert_eq!(poller.pull_events_array(&mut events, 1000), Ok(2));
+            for (fd, events, ctx) in events.iter() {
+                assert_eq!(*events, Events::new().write());
+                assert_eq!(*ctx, Some(fd));
+            }
+            // 空数组不会以 `maxevents` 为 0 调用 `epoll_wait`。
+            let mut empty: [EventData<'_, i32>; 0] = [];
+            assert_eq!(poller.pull_events_array(&mut empty, 1000), Ok(0));
+            for fd in fds.iter() {
+                libc::close(*fd);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wait_one() {
+        unsafe {
+            let mut fds = [0; 4];
+            let mut poller = Poller::new().unwrap();
+            for pair in fds.chunks_mut(2) {
+                assert_eq!(libc::pipe(pair.as_mut_ptr()), 0);
+                assert!(poller.add(pair[1], Events::new().write(), None).is_ok());
+            }
+            let mut seen = Vec::new();
+            for _ in 0..4 {
+                seen.push(poller.wait_one(1000).unwrap().unwrap().0);
+            }
+            // 水平触发模式下两个 `fd` 都会被轮流返回。
+            assert!(seen.contains(&fds[1]) && seen.contains(&fds[3]));
+            for pair in fds.chunks(2) {
+                assert!(poller.remove(pair[1]).is_ok());
+            }
+            assert!(poller.wait_one(0).unwrap().is_none());
+            for fd in fds.iter() {
+                libc::close(*fd);
+            }
+        }
+    }
+
+    #[test]
+    fn test_two_pollers() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(
+                libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()),
+                0
+            );
+            let mut reader = Poller::new().unwrap();
+            let mut writer = Poller::new().unwrap();
+            assert!(reader.add(fds[0], Events::new().read(), None).is_ok());
+            assert!(writer.add(fds[0], Events::new().write(), None).is_ok());
+            assert!(reader.pull_events(0).unwrap().is_empty());
+            let events = writer.pull_events(1000).unwrap();
+            assert_eq!((events[0].0, events[0].1), (fds[0], Events::new().write()));
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), 1);
+            let events = reader.pull_events(1000).unwrap();
+            assert_eq!((events[0].0, events[0].1), (fds[0], Events::new().read()));
+            assert_eq!(writer.pull_events(0).unwrap()[0].1, Events::new().write());
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_edge_preserved() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(
+                libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()),
+                0
+            );
+            let mut poller = Poller::new().unwrap();
+            let events = Events::new().read().edge_triggered();
+            assert!(poller.add(fds[0], events, None).is_ok());
+            assert!(poller
+                .modify(fds[0], Events::new().read().write(), None)
+                .is_ok());
+            assert_eq!(poller.events_for(fds[0]), Some(events.write()));
+            // 边沿触发模式下可写事件只报告一次。
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            assert!(poller.set_edge_triggered(fds[0], false).is_ok());
+            assert_eq!(
+                poller.events_for(fds[0]),
+                Some(Events::new().read().write())
+            );
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_add_batch() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = TypedPoller::<u32>::new().unwrap();
+            let results = poller.add_batch(vec![
+                (fds[0], Events::new().read(), Some(0)),
+                (i32::MAX, Events::new().read(), Some(1)),
+                (fds[1], Events::new().write(), Some(2)),
+            ]);
+            assert_eq!(results.len(), 3);
+            assert!(results[0].1.is_ok());
+            assert_eq!(results[1], (i32::MAX, Err(SysError::from(libc::EBADF))));
+            assert!(results[2].1.is_ok());
+            assert_eq!(poller.len(), 2);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_pull_events_status() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = TypedPoller::<u32>::new().unwrap();
+            assert!(matches!(
+                poller.pull_events_status(0),
+                Ok(WaitStatus::Timeout)
+            ));
+            assert!(poller.wake().is_ok());
+            match poller.pull_events_status(1000) {
+                Ok(WaitStatus::Events(events)) => assert!(events.is_empty()),
+                other => panic!("unexpected {:?}", other),
+            }
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            match poller.pull_events_status(1000) {
+                Ok(WaitStatus::Events(events)) => assert_eq!(events.len(), 1),
+                other => panic!("unexpected {:?}", other),
+            }
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_try_clone() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = TypedPoller::<u32>::new().unwrap();
+            assert!(poller.add(fds[1], Events::new().write(), Some(7)).is_ok());
+            let monitor = poller.try_clone().unwrap();
+            assert_ne!(monitor.as_raw_fd(), poller.as_raw_fd());
+            assert_eq!(monitor.get_context(fds[1]), Some(&7));
+            let events = monitor.pull_events(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].0, fds[1]);
+            assert!(poller.wake().is_ok());
+            assert!(monitor.pull_events(1000).unwrap().len() <= 1);
+            drop(poller);
+            assert_eq!(monitor.pull_events(1000).unwrap().len(), 1);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "negative fd")]
+    fn test_add_negative_fd() {
+        let mut poller = Poller::new().unwrap();
+        let _ = poller.add(-1, Events::new().read(), None);
+    }
+
+    #[test]
+    fn test_pull_events_unknown_fd() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let poller = TypedPoller::<u32>::new().unwrap();
+            // 绕过监视列表直接注册到内核，模拟事件数据找不到对应监视项的情况。
+            assert!(poller
+                .ctl(libc::EPOLL_CTL_ADD, fds[1], Events::new().write())
+                .is_ok());
+            let events = poller.pull_events(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].0, fds[1]);
+            assert!(events[0].1.has_write());
+            assert!(events[0].2.is_none());
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_drain() {
+        unsafe {
+            let mut pipes = [[0; 2]; 3];
+            let mut poller = TypedPoller::<u32>::new().unwrap().with_max_events(1);
+            for fds in pipes.iter_mut() {
+                assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+                assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            }
+            let events = poller.drain(1000, 16).unwrap();
+            let mut fds: Vec<i32> = events.iter().map(|x| x.0).collect();
+            fds.sort_unstable();
+            let mut expected: Vec<i32> = pipes.iter().map(|x| x[1]).collect();
+            expected.sort_unstable();
+            assert_eq!(fds, expected);
+            assert_eq!(poller.drain(1000, 2).unwrap().len(), 2);
+            assert!(poller.drain(1000, 0).unwrap().is_empty());
+            for fds in pipes.iter() {
+                libc::close(fds[0]);
+                libc::close(fds[1]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_interest() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            let err = poller.add(fds[1], Events::new(), None).unwrap_err();
+            assert_eq!(
+                (err.code(), err.context()),
+                (libc::EINVAL, Some("empty interest mask"))
+            );
+            let err = poller
+                .add(fds[1], Events::new().edge_triggered(), None)
+                .unwrap_err();
+            assert_eq!(err.context(), Some("empty interest mask"));
+            // 挂起及错误事件总会被报告，不算作关注的事件。
+            let hangup = Events::from(libc::EPOLLHUP as u32);
+            for events in [
+                hangup,
+                Events::new().error(),
+                hangup.union(Events::new().error()),
+            ] {
+                let err = poller.add(fds[1], events, None).unwrap_err();
+                assert_eq!(err.context(), Some("empty interest mask"));
+            }
+            assert!(!poller.contains(fds[1]));
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            let err = poller
+                .modify(fds[1], Events::new().one_shot(), None)
+                .unwrap_err();
+            assert_eq!(err.context(), Some("empty interest mask"));
+            let err = poller
+                .modify(fds[1], Events::new().error(), None)
+                .unwrap_err();
+            assert_eq!(err.context(), Some("empty interest mask"));
+            assert_eq!(poller.events_for(fds[1]), Some(Events::new().write()));
+            assert!(poller
+                .add(fds[1], Events::new().error().read_hangup(), None)
+                .is_ok());
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_add_dup() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = TypedPoller::<u32>::new().unwrap();
+            let dup = poller
+                .add_dup(fds[1], Events::new().write(), Some(5))
+                .unwrap();
+            libc::close(fds[1]);
+            let events = poller.pull_events(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!((events[0].0, events[0].2), (dup, Some(&5)));
+            drop(events);
+            assert_eq!(poller.remove(dup), Ok(Some(5)));
+            assert_eq!(
+                poller.add_dup(i32::MAX, Events::new().write(), None),
+                Err(SysError::from(libc::EBADF))
+            );
+            libc::close(fds[0]);
+        }
+    }
+
+    #[test]
+    fn test_set_write_interest() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = TypedPoller::<u32>::new().unwrap();
+            assert!(poller.add(fds[1], Events::new().read(), None).is_ok());
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            assert_eq!(poller.set_write_interest(fds[1], true), Ok(true));
+            assert_eq!(poller.set_write_interest(fds[1], true), Ok(false));
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            assert_eq!(poller.set_write_interest(fds[1], false), Ok(true));
+            assert_eq!(poller.set_write_interest(fds[1], false), Ok(false));
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            assert!(poller.disable(fds[1]).is_ok());
+            assert_eq!(poller.set_write_interest(fds[1], true), Ok(false));
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            assert!(poller.enable(fds[1]).is_ok());
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            assert_eq!(
+                poller.set_write_interest(fds[0], true),
+                Err(SysError::from(libc::ENOENT))
+            );
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_ready_event() {
+        let a = ReadyEvent {
+            fd: 1,
+            events: Events::new().write(),
+        };
+        let b = ReadyEvent {
+            fd: 1,
+            events: Events::new().read(),
+        };
+        let c = ReadyEvent {
+            fd: 0,
+            events: Events::new().write(),
+        };
+        let mut sorted = vec![a, b, c];
+        sorted.sort();
+        assert_eq!(sorted, vec![c, b, a]);
+        let data: EventData<'_, u32> = (1, Events::new().write(), None);
+        assert_eq!(ReadyEvent::from(&data), a);
+    }
+
+    #[test]
+    fn test_remove_closed() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = TypedPoller::<u32>::new().unwrap();
+            assert!(poller.add(fds[1], Events::new().write(), Some(3)).is_ok());
+            libc::close(fds[1]);
+            assert_eq!(poller.remove(fds[1]), Ok(Some(3)));
+            assert!(!poller.contains(fds[1]));
+            assert_eq!(poller.remove(fds[1]), Err(SysError::from(libc::ENOENT)));
+            libc::close(fds[0]);
+        }
+    }
+
+    #[test]
+    fn test_run_until() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = TypedPoller::<u32>::new().unwrap();
+            assert!(poller.add(fds[1], Events::new().write(), Some(1)).is_ok());
+            let mut idle = 0;
+            let mut seen = Vec::new();
+            let result = poller.run_until(
+                0,
+                || {
+                    idle += 1;
+                    true
+                },
+                |(fd, events, ctx)| {
+                    seen.push((fd, events, ctx.copied()));
+                    seen.len() < 3
+                },
+            );
+            assert!(result.is_ok());
+            assert_eq!(idle, 0);
+            assert_eq!(seen, vec![(fds[1], Events::new().write(), Some(1)); 3]);
+            assert!(poller.remove(fds[1]).is_ok());
+            assert!(poller.run_until(0, || false, |_| true).is_ok());
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let poller = Poller::new().unwrap();
+            let interest = Events::new().read();
+            assert_eq!(poller.check(fds[0], interest, 0), Ok(Events::new()));
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), 1);
+            assert_eq!(poller.check(fds[0], interest, 0), Ok(interest));
+            assert!(!poller.contains(fds[0]));
+            libc::close(fds[1]);
+            let ready = poller.check(fds[0], interest, 0).unwrap();
+            assert!(ready.has_read() && ready.has_hangup());
+            libc::close(fds[0]);
+            assert_eq!(
+                poller.check(i32::MAX, interest, 0),
+                Err(SysError::from(libc::EBADF))
+            );
+        }
+    }
+
+    #[test]
+    fn test_reconcile() {
+        unsafe {
+            let mut fds = [0; 4];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            assert_eq!(libc::pipe(fds[2..].as_mut_ptr()), 0);
+            let mut poller = TypedPoller::<u32>::new().unwrap();
+            assert!(poller.add(fds[0], Events::new().read(), Some(0)).is_ok());
+            assert!(poller.add(fds[1], Events::new().read(), Some(1)).is_ok());
+            let mut desired = FdMap::new();
+            desired.insert(fds[1], (Events::new().write(), Some(11)));
+            desired.insert(fds[3], (Events::new().write(), Some(2)));
+            assert!(poller.reconcile(&desired).is_ok());
+            assert_eq!(poller.len(), 2);
+            assert!(!poller.contains(fds[0]));
+            assert_eq!(poller.events_for(fds[1]), Some(Events::new().write()));
+            assert_eq!(poller.get_context(fds[1]), Some(&11));
+            assert_eq!(poller.get_context(fds[3]), Some(&2));
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 2);
+            desired.insert(fds[1], (Events::new().write(), Some(12)));
+            desired.insert(i32::MAX, (Events::new().read(), None));
+            assert_eq!(poller.reconcile(&desired), Err(SysError::from(libc::EBADF)));
+            assert_eq!(poller.get_context(fds[1]), Some(&12));
+            assert!(!poller.contains(i32::MAX));
+            desired.remove(&i32::MAX);
+            // 变化的事件集合经过 `modify` 的校验：保留边沿触发模式，拒绝独占唤醒标志。
+            assert!(poller.set_edge_triggered(fds[1], true).is_ok());
+            desired.insert(fds[1], (Events::new().read().write(), Some(13)));
+            assert!(poller.reconcile(&desired).is_ok());
+            assert_eq!(
+                poller.events_for(fds[1]),
+                Some(Events::new().read().write().edge_triggered())
+            );
+            desired.insert(fds[3], (Events::new().read().exclusive(), Some(3)));
+            assert_eq!(
+                poller.reconcile(&desired),
+                Err(SysError::from(libc::EINVAL))
+            );
+            assert_eq!(poller.events_for(fds[3]), Some(Events::new().write()));
+            for fd in fds.iter() {
+                libc::close(*fd);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pull_events_cancellable() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = TypedPoller::<u32>::new().unwrap();
+            let cancel = AtomicBool::new(false);
+            let start = Instant::now();
+            assert!(matches!(
+                poller.pull_events_cancellable(120, &cancel),
+                Ok(CancellableStatus::Timeout)
+            ));
+            assert!(start.elapsed() >= Duration::from_millis(120));
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            match poller.pull_events_cancellable(-1, &cancel) {
+                Ok(CancellableStatus::Events(events)) => assert_eq!(events.len(), 1),
+                other => panic!("unexpected {:?}", other),
+            }
+            assert!(poller.remove(fds[1]).is_ok());
+            let poller = Arc::new(poller);
+            let cancel = Arc::new(AtomicBool::new(false));
+            let flag = Arc::clone(&cancel);
+            let handle = std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(100));
+                flag.store(true, Ordering::Release);
+            });
+            assert!(matches!(
+                poller.pull_events_cancellable(-1, &cancel),
+                Ok(CancellableStatus::Cancelled)
+            ));
+            handle.join().unwrap();
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_disable() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = TypedPoller::<u32>::new().unwrap();
+            assert!(poller.add(fds[1], Events::new().write(), Some(1)).is_ok());
+            assert!(poller.disable(fds[1]).is_ok());
+            assert!(poller.is_disabled(fds[1]));
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            assert_eq!(poller.events_for(fds[1]), Some(Events::new().write()));
+            assert_eq!(poller.get_context(fds[1]), Some(&1));
+            assert!(poller.enable(fds[1]).is_ok());
+            assert!(!poller.is_disabled(fds[1]));
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            assert_eq!(poller.disable(fds[0]), Err(SysError::from(libc::ENOENT)));
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_disable_set_edge_triggered() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            assert!(poller.disable(fds[1]).is_ok());
+            // 暂停期间只更新保存的事件集合，不会恢复监视。
+            assert!(poller.set_edge_triggered(fds[1], true).is_ok());
+            assert!(poller.is_disabled(fds[1]));
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            assert_eq!(
+                poller.events_for(fds[1]),
+                Some(Events::new().write().edge_triggered())
+            );
+            assert!(poller.enable(fds[1]).is_ok());
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            // 边沿触发已生效，没有新的状态变化时不会再次报告。
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_disable_rearm() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller
+                .add(fds[1], Events::new().write().one_shot(), None)
+                .is_ok());
+            assert!(poller.disable(fds[1]).is_ok());
+            assert!(poller.rearm(fds[1]).is_ok());
+            assert!(poller.is_disabled(fds[1]));
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            assert!(poller.enable(fds[1]).is_ok());
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_disable_add() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            assert!(poller.disable(fds[1]).is_ok());
+            // 重新添加会恢复监视，保存的状态与内核一致。
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            assert!(!poller.is_disabled(fds[1]));
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_clear() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[0], Events::new().read(), None).is_ok());
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            assert!(poller.clear().is_ok());
+            assert!(poller.is_empty());
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            libc::close(fds[1]);
+            assert!(poller.add(fds[0], Events::new().read(), None).is_ok());
+            // 已被关闭的 `fd` 与 `remove` 一样视为已经移除。
+            assert!(poller.clear().is_ok());
+            assert!(poller.is_empty());
+            libc::close(fds[0]);
+        }
+    }
+
+    #[test]
+    fn test_wake() {
+        let poller = Arc::new(Poller::new().unwrap());
+        let waker = Arc::clone(&poller);
+        let thread = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            waker.wake().unwrap();
+        });
+        assert!(poller.pull_events(-1).unwrap().is_empty());
+        thread.join().unwrap();
+        poller.wake().unwrap();
+        poller.wake().unwrap();
+        assert!(poller.pull_events(1000).unwrap().is_empty());
+        assert!(poller.pull_events(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Poller>();
+    }
+
+    #[test]
+    fn test_register() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            {
+                let mut reg = poller.register(fds[0], Events::new().read(), None).unwrap();
+                assert_eq!(reg.fd(), fds[0]);
+                let reg2 = reg.register(fds[1], Events::new().write(), None).unwrap();
+                assert_eq!(reg2.len(), 2);
+            }
+            assert!(poller.is_empty());
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_typed_poller() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = TypedPoller::<u32>::new().unwrap();
+            assert!(poller.add(fds[1], Events::new().write(), Some(7)).is_ok());
+            let events = poller.pull_events(1000).unwrap();
+            assert_eq!(events, vec![(fds[1], Events::new().write(), Some(&7))]);
+            assert_eq!(poller.get_context(fds[1]), Some(&7));
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            let mut count = 0;
+            let mut handlers = FdMap::new();
+            handlers.insert(fds[1], |fd: i32, events: Events| {
+                assert_eq!(events, Events::new().write());
+                count += 1;
+                fd < 0
+            });
+            assert!(poller.run(&mut handlers, 1000).is_ok());
+            drop(handlers);
+            assert_eq!(count, 1);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_token() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[0], Events::new().read(), None).is_ok());
+            assert_eq!(poller.token(fds[0]), Some(fds[0] as u64));
+            // 令牌不要求唯一，可以与其它 `fd` 的令牌相同。
+            assert!(poller
+                .add_with_token(fds[1], Events::new().write(), fds[0] as u64, None)
+                .is_ok());
+            let events = poller.pull_events_with_tokens(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!((events[0].0, events[0].1), (fds[0] as u64, fds[1]));
+            let token = u64::MAX;
+            assert!(poller
+                .add_with_token(fds[1], Events::new().write(), token, None)
+                .is_ok());
+            assert!(poller.modify(fds[1], Events::new().write(), None).is_ok());
+            assert_eq!(poller.token(fds[1]), Some(token));
+            let events = poller.pull_events_with_tokens(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!((events[0].0, events[0].1), (token, fds[1]));
+            assert_eq!(poller.pull_events(1000).unwrap()[0].0, fds[1]);
+            // 其它 `fd` 的令牌与 `fd` 编号相同时，`add` 不受影响。
+            assert!(poller.remove(fds[0]).is_ok());
+            assert!(poller
+                .add_with_token(fds[1], Events::new().write(), fds[0] as u64, None)
+                .is_ok());
+            assert!(poller.add(fds[0], Events::new().read(), None).is_ok());
+            assert_eq!(poller.token(fds[0]), Some(fds[0] as u64));
+            let events = poller.pull_events_with_tokens(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!((events[0].0, events[0].1), (fds[0] as u64, fds[1]));
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_readable() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::task::Wake;
+
+        struct Flag(AtomicBool);
+
+        impl Wake for Flag {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let poller = Poller::new().unwrap();
+            let flag = Arc::new(Flag(AtomicBool::new(false)));
+            let waker = Waker::from(Arc::clone(&flag));
+            let mut cx = Context::from_waker(&waker);
+            let mut readable = poller.readable(fds[0]);
+            assert!(Pin::new(&mut readable).poll(&mut cx).is_pending());
+            assert!(poller.pull_events(10).unwrap().is_empty());
+            assert!(!flag.0.load(Ordering::SeqCst));
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), 1);
+            assert!(poller.pull_events(1000).unwrap().is_empty());
+            assert!(flag.0.load(Ordering::SeqCst));
+            match Pin::new(&mut readable).poll(&mut cx) {
+                Poll::Ready(Ok(events)) => assert!(events.has_read()),
+                x => panic!("unexpected: {:?}", x),
+            }
+            drop(readable);
+            // 完成后已取消注册，可以再次等待同一个 `fd`。
+            let mut readable = poller.readable(fds[0]);
+            assert!(Pin::new(&mut readable).poll(&mut cx).is_pending());
+            drop(readable);
+            assert!(poller.readers.lock().unwrap().is_empty());
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_events_iter() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            let mut iter = poller.events(1000);
+            for _ in 0..3 {
+                assert_eq!(iter.next().map(|x| x.0), Some(fds[1]));
+            }
+            assert_eq!(iter.last_error(), None);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+            // 使用无效的 epoll 文件描述符，迭代因错误结束。
+            let poller = Poller::from_raw_fd(-1);
+            let mut iter = poller.events(0);
+            assert!(iter.next().is_none());
+            assert_eq!(iter.last_error(), Some(SysError::from(libc::EBADF)));
+            assert_eq!(iter.last_error().unwrap().context(), Some("epoll_wait"));
+        }
+    }
+
+    #[test]
+    fn test_pull_events_pwait() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[0], Events::new().read(), None).is_ok());
+            let mut sigmask = std::mem::zeroed();
+            libc::sigemptyset(&mut sigmask);
+            assert!(poller.pull_events_pwait(10, &sigmask).unwrap().is_empty());
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), 1);
+            let events = poller.pull_events_pwait(1000, &sigmask).unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!((events[0].0, events[0].1), (fds[0], Events::new().read()));
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_pull_events_deadline() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[0], Events::new().read(), None).is_ok());
+            let timeout = Duration::from_micros(200);
+            assert!(poller.pull_events_deadline(timeout).unwrap().is_empty());
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), 1);
+            let events = poller.pull_events_deadline(Duration::from_secs(1)).unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].0, fds[0]);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[cfg(feature = "stats")]
+    #[test]
+    fn test_stats() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert_eq!(poller.stats(), PollerStats::default());
+            assert!(poller.add(fds[0], Events::new().read(), None).is_ok());
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            assert!(poller.remove(fds[1]).is_ok());
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            let stats = poller.stats();
+            assert_eq!((stats.waits, stats.events, stats.timeouts), (2, 1, 1));
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_drop_fd_zero() {
+        unsafe {
+            let epoll_fd = epoll_create1(libc::EPOLL_CLOEXEC);
+            assert!(epoll_fd >= 0);
+            // 在子进程中把 epoll 文件描述符放到 0 上，避免影响测试进程的标准输入。
+            let mut poller = Poller::from_raw_fd(-1);
+            let pid = libc::fork();
+            assert!(pid >= 0);
+            if pid == 0 {
+                if libc::dup2(epoll_fd, 0) != 0 {
+                    libc::_exit(2);
+                }
+                poller.epoll_fd = 0;
+                drop(poller);
+                let closed = libc::fcntl(0, libc::F_GETFD) == -1;
+                libc::_exit(if closed { 0 } else { 1 });
+            }
+            let mut status = 0;
+            assert_eq!(libc::waitpid(pid, &mut status, 0), pid);
+            assert!(libc::WIFEXITED(status));
+            assert_eq!(libc::WEXITSTATUS(status), 0);
+            poller.epoll_fd = epoll_fd;
+        }
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let mut poller = Poller::with_capacity(100).unwrap();
+        assert!(poller.watches.slots.capacity() >= 100);
+        assert!(poller.scratch.get_mut().unwrap().capacity() >= 101);
+        assert!(poller.is_empty());
+        assert!(poller.wake().is_ok());
+    }
+
+    #[test]
+    fn test_prune() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            // 使用较大的编号，避免被并行执行的其它测试复用。
+            let fd = libc::fcntl(fds[0], libc::F_DUPFD_CLOEXEC, 1000);
+            assert!(fd >= 1000);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fd, Events::new().read(), None).is_ok());
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            assert_eq!(poller.prune(), 0);
+            libc::close(fd);
+            assert_eq!(poller.prune(), 1);
+            assert!(!poller.contains(fd));
+            assert!(poller.contains(fds[1]));
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_watch_table() {
+        let mut table = WatchTable::<()>::default();
+        assert!(table.get(-1).is_none());
+        table.insert(5, Watch::new(Events::new().read(), 5, None));
+        table.insert(5, Watch::new(Events::new().write(), 50, None));
+        table.insert(2, Watch::new(Events::new().read(), 2, None));
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.get(5).map(|w| w.events), Some(Events::new().write()));
+        assert_eq!(table.get(5).map(|w| w.token), Some(50));
+        assert_eq!(
+            table.iter().map(|(fd, _)| fd).collect::<Vec<_>>(),
+            vec![2, 5]
+        );
+        assert!(table.remove(5).is_some());
+        assert!(table.remove(5).is_none());
+        assert!(table.remove(-1).is_none());
+        assert_eq!(table.len(), 1);
+        table.clear();
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_take_error() {
+        unsafe {
+            let poller = Poller::new().unwrap();
+            let mut fds = [0; 2];
+            assert_eq!(
+                libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()),
+                0
+            );
+            assert_eq!(poller.take_error(fds[0]), Ok(None));
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            assert_eq!(
+                poller.take_error(fds[0]),
+                Err(SysError::from(libc::ENOTSOCK))
+            );
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_modify() {
+        unsafe {
+            let cstr = std::ffi::CString::new("/proc/uptime").unwrap();
+            let fd = libc::open(cstr.as_ptr(), libc::O_RDONLY);
+            let mut poller = Poller::new().unwrap();
+            assert_eq!(
+                poller.modify(fd, Events::new().read(), None),
+                Err(SysError::from(libc::ENOENT))
+            );
+            assert!(poller.add(fd, Events::new().write(), None).is_ok());
+            assert!(poller.modify(fd, Events::new().read(), None).is_ok());
+            let events = poller.pull_events(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert!(events[0].1.has_read());
+            assert!(poller.remove(fd).is_ok());
+            libc::close(fd);
+        }
+    }
+
+    #[test]
+    fn test_events_conversion() {
+        let flags = [
+            (Events::new().read(), libc::EPOLLIN),
+            (Events::new().write(), libc::EPOLLOUT),
+            (Events::new().error(), libc::EPOLLERR),
+            (Events::new().with(Event::HangUp), libc::EPOLLHUP),
+            (Events::new().read_hangup(), libc::EPOLLRDHUP),
+            (Events::new().priority(), libc::EPOLLPRI),
+            (Events::new().one_shot(), libc::EPOLLONESHOT),
+            (Events::new().edge_triggered(), libc::EPOLLET),
+        ];
+        for &(events, flag) in flags.iter() {
+            assert_eq!(u32::from(events), flag as u32);
+            let event = Event::ALL.iter().find(|&&e| Events::from(e) == events);
+            assert_eq!(event.map(|e| e.as_epoll_flag()), Some(flag as u32));
+            assert_eq!(Events::from(flag as u32), events);
+            assert_eq!(events.to_epoll_flags(), flag as u32);
+            assert_eq!(Events::from_epoll_flags(flag as u32), events);
+        }
+    }
+
+    #[test]
+    fn test_one_shot() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller
+                .add(fds[1], Events::new().write().one_shot(), None)
+                .is_ok());
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            assert!(poller.rearm(fds[1]).is_ok());
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            assert_eq!(poller.rearm(fds[0]), Err(SysError::from(libc::ENOENT)));
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_edge_triggered() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller
+                .add(fds[0], Events::new().read().edge_triggered(), None)
+                .is_ok());
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), 1);
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_read_hangup() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(
+                libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()),
+                0
+            );
+            let mut poller = Poller::new().unwrap();
+            assert!(poller
+                .add(fds[0], Events::new().read_hangup(), None)
+                .is_ok());
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            libc::shutdown(fds[1], libc::SHUT_WR);
+            let events = poller.pull_events(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert!(events[0].1.has_read_hangup());
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_hangup() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[0], Events::new().read(), None).is_ok());
+            libc::close(fds[1]);
+            let events = poller.pull_events(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert!(events[0].1.has_hangup());
+            assert!(Events::from(libc::EPOLLHUP as u32).has_hangup());
+            libc::close(fds[0]);
+        }
+    }
+
+    #[test]
+    fn test_exclusive() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            let events = Events::new().read().exclusive();
+            assert!(poller.add(fds[0], events, None).is_ok());
+            assert_eq!(
+                poller.modify(fds[0], events, None),
+                Err(SysError::from(libc::EINVAL))
+            );
+            assert_eq!(poller.events_for(fds[0]), Some(events));
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), 1);
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_add_poller() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut child = TypedPoller::<u32>::new().unwrap();
+            assert!(child.add(fds[0], Events::new().read(), Some(1)).is_ok());
+            let mut parent = Poller::new().unwrap();
+            assert!(parent.add_poller(&child, None).is_ok());
+            assert!(parent.pull_events(0).unwrap().is_empty());
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), 1);
+            let events = parent.pull_events(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].0, child.as_raw_fd());
+            let events = child.pull_events(0).unwrap();
+            assert_eq!(events, vec![(fds[0], Events::new().read(), Some(&1))]);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_interrupted() {
+        extern "C" fn handler(_: i32) {}
+        unsafe {
+            // 保存原有的信号处理方式，测试结束前恢复，避免影响同一进程中并行运行的其它测试。
+            let mut sa: libc::sigaction = std::mem::zeroed();
+            let mut old: libc::sigaction = std::mem::zeroed();
+            sa.sa_sigaction = handler as *const () as usize;
+            assert_eq!(libc::sigaction(libc::SIGUSR1, &sa, &mut old), 0);
+            let thread = libc::pthread_self();
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[0], Events::new().read(), None).is_ok());
+            let killer = std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                libc::pthread_kill(thread, libc::SIGUSR1);
+            });
+            let err = poller.pull_events_once(1000).unwrap_err();
+            assert!(err.is_interrupted());
+            killer.join().unwrap();
+            let killer = std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                libc::pthread_kill(thread, libc::SIGUSR1);
+            });
+            assert!(poller.pull_events(200).unwrap().is_empty());
+            killer.join().unwrap();
+            assert_eq!(
+                libc::sigaction(libc::SIGUSR1, &old, std::ptr::null_mut()),
+                0
+            );
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_cloexec() {
+        let poller = Poller::new().unwrap();
+        let flags = unsafe { libc::fcntl(poller.as_raw_fd(), libc::F_GETFD) };
+        assert_eq!(flags & libc::FD_CLOEXEC, libc::FD_CLOEXEC);
+        let poller = Poller::new_with_flags(0).unwrap();
+        let flags = unsafe { libc::fcntl(poller.epoll_fd, libc::F_GETFD) };
+        assert_eq!(flags & libc::FD_CLOEXEC, 0);
+    }
+
+    #[test]
+    fn test_raw_fd() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let epoll_fd = Poller::new().unwrap().into_raw_fd();
+            assert!(libc::fcntl(epoll_fd, libc::F_GETFD) >= 0);
+            let mut poller = Poller::from_raw_fd(epoll_fd);
+            assert_eq!(poller.as_raw_fd(), epoll_fd);
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            // 新注册的唤醒 eventfd 可用，唤醒事件不会出现在结果中。
+            assert!(poller.remove(fds[1]).is_ok());
+            assert!(poller.wake().is_ok());
+            assert!(poller.pull_events(1000).unwrap().is_empty());
+            drop(poller);
+            // 无效的 epoll 文件描述符无法注册唤醒 eventfd。
+            let poller = Poller::from_raw_fd(-1);
+            assert_eq!(poller.wake(), Err(SysError::from(libc::EBADF)));
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn test_timeout_to_ms() {
+        assert_eq!(timeout_to_ms(None), -1);
+        assert_eq!(timeout_to_ms(Some(Duration::from_millis(0))), 0);
+        assert_eq!(timeout_to_ms(Some(Duration::from_micros(1))), 1);
+        assert_eq!(timeout_to_ms(Some(Duration::from_millis(1500))), 1500);
+        assert_eq!(timeout_to_ms(Some(Duration::from_secs(u64::MAX))), i32::MAX);
+    }
+}
@@ -0,0 +1,154 @@
+//! Linux 输入设备（evdev）。
+//!
+use crate::SysError;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::path::Path;
+
+/// 定义以非阻塞方式读取 `input_event` 记录的输入设备。
+///
+/// 每次 `read_events` 都会读出所有已排队的记录，因此也适用于边沿触发模式；
+/// 不完整的记录会被保留下来，与下一次读到的数据拼接。
+///
+/// # Examples
+///
+/// ```no_run
+/// use poller::{EvDev, Events, Poller};
+/// let mut evdev = EvDev::open("/dev/input/event0").unwrap();
+/// let mut poller = Poller::new().unwrap();
+/// poller.add_source(&evdev, Events::new().read().edge_triggered(), None).unwrap();
+/// poller.pull_events(-1).unwrap();
+/// for event in evdev.read_events().unwrap() {
+///     println!("Type={}, Code={}, Value={}", event.type_, event.code, event.value);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct EvDev {
+    fd: OwnedFd,
+    pending: Vec<u8>,
+}
+
+impl AsRawFd for EvDev {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl From<OwnedFd> for EvDev {
+    /// 使用已打开的 `fd` 创建输入设备，调用者需要确保 `fd` 为非阻塞模式。
+    fn from(fd: OwnedFd) -> Self {
+        Self {
+            fd,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl EvDev {
+    /// 以只读、非阻塞方式打开 `path` 指定的输入设备。
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SysError> {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())
+            .map_err(|_| SysError::from(libc::EINVAL))?;
+        let fd = unsafe {
+            libc::open(
+                path.as_ptr(),
+                libc::O_RDONLY | libc::O_NONBLOCK | libc::O_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            Err(SysError::last().with_context("open"))
+        } else {
+            Ok(Self::from(unsafe { OwnedFd::from_raw_fd(fd) }))
+        }
+    }
+
+    /// 读取所有已排队的 `input_event` 记录，直到设备返回 `EAGAIN` 或文件结束。
+    ///
+    /// 没有可读的记录时返回空列表。
+    pub fn read_events(&mut self) -> Result<Vec<libc::input_event>, SysError> {
+        const SIZE: usize = std::mem::size_of::<libc::input_event>();
+        let mut buf = [0u8; SIZE * 64];
+        loop {
+            let n = unsafe { libc::read(self.as_raw_fd(), buf.as_mut_ptr() as *mut _, buf.len()) };
+            if n < 0 {
+                let err = SysError::last();
+                if err.code() == libc::EAGAIN {
+                    break;
+                }
+                if err.is_interrupted() {
+                    continue;
+                }
+                return Err(err);
+            }
+            if n == 0 {
+                break;
+            }
+            self.pending.extend_from_slice(&buf[..n as usize]);
+        }
+        let complete = self.pending.len() / SIZE * SIZE;
+        let events = self.pending[..complete]
+            .chunks_exact(SIZE)
+            .map(|chunk| unsafe {
+                std::ptr::read_unaligned(chunk.as_ptr() as *const libc::input_event)
+            })
+            .collect();
+        self.pending.drain(..complete);
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input_event(code: u16, value: i32) -> libc::input_event {
+        libc::input_event {
+            time: libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
+            type_: 1,
+            code,
+            value,
+        }
+    }
+
+    fn as_bytes(event: &libc::input_event) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(
+                event as *const libc::input_event as *const u8,
+                std::mem::size_of::<libc::input_event>(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_read_events() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(
+                libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK | libc::O_CLOEXEC),
+                0
+            );
+            let mut evdev = EvDev::from(OwnedFd::from_raw_fd(fds[0]));
+            assert_eq!(evdev.read_events().map(|x| x.len()), Ok(0));
+            let mut data = Vec::new();
+            for code in 0..3 {
+                data.extend_from_slice(as_bytes(&input_event(code, 1)));
+            }
+            // 最后一条记录只写入一半，模拟不完整的读取。
+            let half = data.len() - std::mem::size_of::<libc::input_event>() / 2;
+            let write = |bytes: &[u8]| libc::write(fds[1], bytes.as_ptr() as *const _, bytes.len());
+            assert_eq!(write(&data[..half]), half as isize);
+            let events = evdev.read_events().unwrap();
+            assert_eq!(events.len(), 2);
+            assert_eq!((events[0].code, events[1].code), (0, 1));
+            assert_eq!(write(&data[half..]), (data.len() - half) as isize);
+            let events = evdev.read_events().unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!((events[0].code, events[0].value), (2, 1));
+            libc::close(fds[1]);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Linux 事件通知文件描述符（eventfd）。
+//!
+use crate::SysError;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+
+/// 定义基于 `eventfd` 的事件计数器。
+///
+/// 计数器不为 0 时文件描述符可读，可以通过 `Poller::add_source` 与其它 `fd` 一起监视，
+/// 常用于跨线程唤醒或作为计数信号量。
+///
+/// # Examples
+///
+/// ```
+/// use poller::EventFd;
+/// let efd = EventFd::new(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK).unwrap();
+/// efd.write(2).unwrap();
+/// efd.write(3).unwrap();
+/// assert_eq!(efd.read().unwrap(), 5);
+/// ```
+#[derive(Debug)]
+pub struct EventFd {
+    fd: OwnedFd,
+}
+
+impl AsRawFd for EventFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl EventFd {
+    /// 创建一个初始值为 `initval` 的事件计数器。
+    ///
+    /// `flags` 可以是 `EFD_CLOEXEC`、`EFD_NONBLOCK` 和 `EFD_SEMAPHORE` 的组合；
+    /// 带有 `EFD_SEMAPHORE` 时每次 `read` 只将计数器减 1 并返回 1，否则返回并清零整个计数器。
+    pub fn new(initval: u32, flags: i32) -> Result<Self, SysError> {
+        let fd = unsafe { libc::eventfd(initval, flags) };
+        if fd < 0 {
+            Err(SysError::last())
+        } else {
+            Ok(Self {
+                fd: unsafe { OwnedFd::from_raw_fd(fd) },
+            })
+        }
+    }
+
+    /// 将 `value` 累加到计数器上。
+    ///
+    /// 计数器将要溢出时，非阻塞模式下返回 `EAGAIN` 错误，否则阻塞到计数器被读取。
+    pub fn write(&self, value: u64) -> Result<(), SysError> {
+        let n = unsafe { libc::write(self.as_raw_fd(), &value as *const u64 as *const _, 8) };
+        if n < 0 {
+            Err(SysError::last())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 读取计数器。
+    ///
+    /// 计数器为 0 时，非阻塞模式下返回 `EAGAIN` 错误，否则阻塞到计数器不为 0。
+    pub fn read(&self) -> Result<u64, SysError> {
+        let mut value = 0u64;
+        let n = unsafe { libc::read(self.as_raw_fd(), &mut value as *mut u64 as *mut _, 8) };
+        if n < 0 {
+            Err(SysError::last())
+        } else {
+            Ok(value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_fd() {
+        let efd = EventFd::new(1, libc::EFD_NONBLOCK).unwrap();
+        efd.write(2).unwrap();
+        assert_eq!(efd.read().unwrap(), 3);
+        assert_eq!(efd.read(), Err(SysError::from(libc::EAGAIN)));
+        let efd = EventFd::new(2, libc::EFD_NONBLOCK | libc::EFD_SEMAPHORE).unwrap();
+        assert_eq!(efd.read().unwrap(), 1);
+        assert_eq!(efd.read().unwrap(), 1);
+        assert_eq!(efd.read(), Err(SysError::from(libc::EAGAIN)));
+    }
+}
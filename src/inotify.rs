@@ -0,0 +1,178 @@
+//! Linux 文件系统事件通知（inotify）。
+//!
+use crate::SysError;
+use std::ffi::{CString, OsString};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::path::Path;
+
+/// 定义一个 `inotify` 事件。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InotifyEvent {
+    /// 产生事件的监视描述符，由 `Inotify::add_watch` 返回。
+    pub wd: i32,
+    /// 事件掩码，例如 `libc::IN_CREATE`、`libc::IN_MODIFY` 等。
+    pub mask: u32,
+    /// 关联同一次重命名的 `IN_MOVED_FROM` 与 `IN_MOVED_TO` 事件的标识。
+    pub cookie: u32,
+    /// 监视目录时为目录内发生事件的文件名，否则为 `None`。
+    pub name: Option<OsString>,
+}
+
+/// 定义基于 `inotify` 的文件系统事件源。
+///
+/// 有事件到达时文件描述符可读，可以通过 `Poller::add_source` 与其它 `fd` 一起监视。
+///
+/// # Examples
+///
+/// ```no_run
+/// use poller::{Events, Inotify, Poller};
+/// let inotify = Inotify::new().unwrap();
+/// inotify.add_watch("/tmp", libc::IN_CREATE | libc::IN_DELETE).unwrap();
+/// let mut poller = Poller::new().unwrap();
+/// poller.add_source(&inotify, Events::new().read(), None).unwrap();
+/// poller.pull_events(-1).unwrap();
+/// for event in inotify.read_events().unwrap() {
+///     println!("Mask={:#x}, Name={:?}", event.mask, event.name);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Inotify {
+    fd: OwnedFd,
+}
+
+impl AsRawFd for Inotify {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl Inotify {
+    /// 创建一个非阻塞的 `inotify` 实例。
+    pub fn new() -> Result<Self, SysError> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC | libc::IN_NONBLOCK) };
+        if fd < 0 {
+            Err(SysError::last().with_context("inotify_init1"))
+        } else {
+            Ok(Self {
+                fd: unsafe { OwnedFd::from_raw_fd(fd) },
+            })
+        }
+    }
+
+    /// 监视 `path` 上 `mask` 指定的事件，返回监视描述符。
+    ///
+    /// 同一个路径重复添加时返回相同的监视描述符，并以新的 `mask` 替换原来的掩码。
+    /// 路径中包含 NUL 字符时返回 `EINVAL` 错误。
+    pub fn add_watch<P: AsRef<Path>>(&self, path: P, mask: u32) -> Result<i32, SysError> {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())
+            .map_err(|_| SysError::from(libc::EINVAL))?;
+        let wd = unsafe { libc::inotify_add_watch(self.as_raw_fd(), path.as_ptr(), mask) };
+        if wd < 0 {
+            Err(SysError::last().with_context("inotify_add_watch"))
+        } else {
+            Ok(wd)
+        }
+    }
+
+    /// 移除监视描述符 `wd`，之后会收到一个 `IN_IGNORED` 事件。
+    pub fn rm_watch(&self, wd: i32) -> Result<(), SysError> {
+        if unsafe { libc::inotify_rm_watch(self.as_raw_fd(), wd) } < 0 {
+            Err(SysError::last().with_context("inotify_rm_watch"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 读取所有已到达的事件，没有待处理的事件时返回 `EAGAIN` 错误。
+    pub fn read_events(&self) -> Result<Vec<InotifyEvent>, SysError> {
+        // 足够容纳至少一个带有最长文件名的事件。
+        let mut buf = vec![0u8; 4096 + libc::PATH_MAX as usize];
+        let n = unsafe { libc::read(self.as_raw_fd(), buf.as_mut_ptr() as *mut _, buf.len()) };
+        if n < 0 {
+            return Err(SysError::last());
+        }
+        Ok(parse_events(&buf[..n as usize]))
+    }
+}
+
+/// 从 `read` 得到的缓冲区中解析出变长的 `inotify_event` 记录。
+///
+/// 每条记录由固定长度的头部及 `len` 字节的文件名组成，文件名以 NUL 结尾并可能被填充对齐；
+/// 缓冲区末尾不完整的记录会被忽略。
+fn parse_events(buf: &[u8]) -> Vec<InotifyEvent> {
+    let header = std::mem::size_of::<libc::inotify_event>();
+    let mut events = Vec::new();
+    let mut offset = 0;
+    while offset + header <= buf.len() {
+        // 缓冲区中的记录不保证按 `inotify_event` 对齐。
+        let raw = unsafe {
+            std::ptr::read_unaligned(buf[offset..].as_ptr() as *const libc::inotify_event)
+        };
+        let start = offset + header;
+        let end = start + raw.len as usize;
+        if end > buf.len() {
+            break;
+        }
+        let name = &buf[start..end];
+        let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(name.len())];
+        events.push(InotifyEvent {
+            wd: raw.wd,
+            mask: raw.mask,
+            cookie: raw.cookie,
+            name: if name.is_empty() {
+                None
+            } else {
+                Some(OsString::from_vec(name.to_vec()))
+            },
+        });
+        offset = end;
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(wd: i32, mask: u32, name: &[u8], len: u32) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&wd.to_ne_bytes());
+        buf.extend_from_slice(&mask.to_ne_bytes());
+        buf.extend_from_slice(&0u32.to_ne_bytes());
+        buf.extend_from_slice(&len.to_ne_bytes());
+        buf.extend_from_slice(name);
+        buf.resize(buf.len() + len as usize - name.len(), 0);
+        buf
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let mut buf = record(1, libc::IN_CREATE, b"a.txt", 16);
+        buf.extend(record(2, libc::IN_DELETE_SELF, b"", 0));
+        buf.extend(record(3, libc::IN_MODIFY, b"truncated", 16)[..20].iter());
+        let events = parse_events(&buf);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].wd, 1);
+        assert_eq!(events[0].mask, libc::IN_CREATE);
+        assert_eq!(events[0].name, Some(OsString::from("a.txt")));
+        assert_eq!(events[1].wd, 2);
+        assert_eq!(events[1].name, None);
+    }
+
+    #[test]
+    fn test_inotify() {
+        let dir = std::env::temp_dir().join(format!("poller-inotify-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let inotify = Inotify::new().unwrap();
+        assert_eq!(inotify.read_events(), Err(SysError::from(libc::EAGAIN)));
+        let wd = inotify.add_watch(&dir, libc::IN_CREATE).unwrap();
+        std::fs::write(dir.join("created"), b"").unwrap();
+        let events = inotify.read_events().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].wd, wd);
+        assert_eq!(events[0].name, Some(OsString::from("created")));
+        assert!(inotify.rm_watch(wd).is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,306 @@
+//! macOS 及 BSD 内核事件队列（kqueue）。
+//!
+use crate::{EventHandler, Events, SysError};
+use std::any::Any;
+use std::collections::HashMap;
+use std::os::unix::io::AsRawFd;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 定义事件关联上下文。
+pub type EventContext = Arc<dyn Any + Send + Sync>;
+
+/// 定义事件数据。
+///
+/// # Fields
+/// * `0` - 触发的文件描述符。
+/// * `1` - 触发的事件集合。
+/// * `2` - 触发的事件对应上下文。
+pub type EventData<'a> = (i32, Events, Option<&'a EventContext>);
+
+/// 定义文件 I/O 事件通知器。
+///
+/// 每个实例可以管理多个 `fd` 的 I/O 事件，读、写事件分别对应 `EVFILT_READ` 和 `EVFILT_WRITE`，
+/// 边沿触发对应 `EV_CLEAR`，单次触发对应 `EV_ONESHOT`。
+#[derive(Debug)]
+pub struct Poller {
+    kq_fd: i32,
+    watches: HashMap<i32, (Events, Option<EventContext>)>,
+}
+
+impl Default for Poller {
+    fn default() -> Self {
+        Self {
+            kq_fd: -1,
+            watches: HashMap::new(),
+        }
+    }
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        if self.kq_fd >= 0 {
+            unsafe {
+                libc::close(self.kq_fd);
+            };
+            self.kq_fd = -1;
+        }
+    }
+}
+
+impl Poller {
+    /// 创建一个新的 I/O 事件通知器。
+    pub fn new() -> Result<Self, SysError> {
+        let kq_fd = unsafe { libc::kqueue() };
+        if kq_fd < 0 {
+            return Err(SysError::last());
+        }
+        let poller = Self {
+            kq_fd,
+            watches: HashMap::new(),
+        };
+        let err = unsafe { libc::fcntl(kq_fd, libc::F_SETFD, libc::FD_CLOEXEC) };
+        if err < 0 {
+            Err(SysError::last())
+        } else {
+            Ok(poller)
+        }
+    }
+
+    /// 添加一个文件描述符到监视列表中。
+    ///
+    /// 如果 `fd` 已经在监视列表中，则更新其事件集合及上下文。
+    ///
+    /// **注意：** 此函数不会把 `fd` 的所有权转移到 `Poller` 内，请确保在 `Poller` 活动期内 `fd` 都是可用的。
+    pub fn add(
+        &mut self,
+        fd: i32,
+        events: Events,
+        ctx: Option<EventContext>,
+    ) -> Result<(), SysError> {
+        let mut flags = libc::EV_ADD | libc::EV_ENABLE;
+        if events.has_edge_triggered() {
+            flags |= libc::EV_CLEAR;
+        }
+        if events.has_one_shot() {
+            flags |= libc::EV_ONESHOT;
+        }
+        let old = self.watches.get(&fd).map(|x| x.0).unwrap_or_default();
+        for &(filter, wanted, registered) in [
+            (libc::EVFILT_READ, events.has_read(), old.has_read()),
+            (libc::EVFILT_WRITE, events.has_write(), old.has_write()),
+        ]
+        .iter()
+        {
+            if wanted {
+                self.change(fd, filter, flags)?;
+            } else if registered {
+                self.delete(fd, filter)?;
+            }
+        }
+        self.watches.insert(fd, (events, ctx));
+        Ok(())
+    }
+
+    /// 添加一个实现了 `AsRawFd` 的对象到监视列表中。
+    ///
+    /// **注意：** 与 `add` 相同，`Poller` 不会持有 `src`，请确保在 `Poller` 活动期内 `src` 都是可用的。
+    pub fn add_source<T: AsRawFd>(
+        &mut self,
+        src: &T,
+        events: Events,
+        ctx: Option<EventContext>,
+    ) -> Result<(), SysError> {
+        self.add(src.as_raw_fd(), events, ctx)
+    }
+
+    /// 修改一个已在监视列表中的文件描述符的事件集合及上下文。
+    ///
+    /// 如果 `fd` 不在监视列表中则返回 `ENOENT` 错误。
+    pub fn modify(
+        &mut self,
+        fd: i32,
+        events: Events,
+        ctx: Option<EventContext>,
+    ) -> Result<(), SysError> {
+        if !self.watches.contains_key(&fd) {
+            return Err(SysError::from(libc::ENOENT));
+        }
+        self.add(fd, events, ctx)
+    }
+
+    /// 将一个文件描述符从监视列表中移除，并返回其关联的上下文（如果有）。
+    pub fn remove(&mut self, fd: i32) -> Result<Option<EventContext>, SysError> {
+        let events = match self.watches.get(&fd) {
+            Some(x) => x.0,
+            None => return Err(SysError::from(libc::ENOENT)),
+        };
+        if events.has_read() {
+            self.delete(fd, libc::EVFILT_READ)?;
+        }
+        if events.has_write() {
+            self.delete(fd, libc::EVFILT_WRITE)?;
+        }
+        Ok(self.watches.remove(&fd).and_then(|(_, ctx)| ctx))
+    }
+
+    /// 将一个通过 `add_source` 添加的对象从监视列表中移除，与 `remove(src.as_raw_fd())` 相同。
+    pub fn remove_source<T: AsRawFd>(&mut self, src: &T) -> Result<Option<EventContext>, SysError> {
+        self.remove(src.as_raw_fd())
+    }
+
+    /// 拉取所有被监测到的 I/O 事件。
+    ///
+    /// 同一个 `fd` 的读、写事件会被合并为一项；等待过程被信号中断（`EINTR`）时会以剩余的超时时间自动重试。
+    pub fn pull_events(&self, timeout_ms: i32) -> Result<Vec<EventData<'_>>, SysError> {
+        let start = Instant::now();
+        loop {
+            let remaining = if timeout_ms < 0 {
+                None
+            } else {
+                let timeout = Duration::from_millis(timeout_ms as u64);
+                Some(timeout.checked_sub(start.elapsed()).unwrap_or_default())
+            };
+            match self.wait(remaining) {
+                Err(err) if err.is_interrupted() => continue,
+                result => return result,
+            }
+        }
+    }
+
+    /// 运行事件循环，把每个就绪的 `fd` 分发给 `handlers` 中对应的处理器。
+    ///
+    /// 任意处理器返回 `false` 时，处理完当前这批事件后停止循环并返回 `Ok(())`。
+    pub fn run<H: EventHandler>(
+        &mut self,
+        handlers: &mut HashMap<i32, H>,
+        timeout_ms: i32,
+    ) -> Result<(), SysError> {
+        loop {
+            let mut running = true;
+            for (fd, events, _) in self.pull_events(timeout_ms)? {
+                if let Some(handler) = handlers.get_mut(&fd) {
+                    running &= handler.handle(fd, events);
+                }
+            }
+            if !running {
+                return Ok(());
+            }
+        }
+    }
+
+    /// 调用一次 `kevent` 并收集就绪的事件。
+    fn wait(&self, timeout: Option<Duration>) -> Result<Vec<EventData<'_>>, SysError> {
+        // 每个 `fd` 最多同时有读、写两个过滤器就绪。
+        let maxevents = (self.watches.len() * 2).max(1);
+        let mut ev: Vec<libc::kevent> = Vec::with_capacity(maxevents);
+        let ts = timeout.map(|d| libc::timespec {
+            tv_sec: d.as_secs() as libc::time_t,
+            tv_nsec: d.subsec_nanos() as libc::c_long,
+        });
+        let ts_ptr = match ts {
+            Some(ref ts) => ts as *const libc::timespec,
+            None => std::ptr::null(),
+        };
+        unsafe {
+            let n = libc::kevent(
+                self.kq_fd,
+                std::ptr::null(),
+                0,
+                ev.as_mut_ptr(),
+                maxevents as libc::c_int,
+                ts_ptr,
+            );
+            if n < 0 {
+                return Err(SysError::last());
+            }
+            ev.set_len(n as usize);
+        }
+        let mut ready: Vec<EventData<'_>> = Vec::with_capacity(ev.len());
+        for x in ev.iter() {
+            let fd = x.ident as i32;
+            let mut events = Events::new();
+            if x.filter == libc::EVFILT_READ {
+                events = events.read();
+                if x.flags & libc::EV_EOF != 0 {
+                    events = events.read_hangup();
+                }
+            } else if x.filter == libc::EVFILT_WRITE {
+                events = events.write();
+            }
+            if x.flags & libc::EV_ERROR != 0 {
+                events = events.error();
+            }
+            match ready.iter_mut().find(|r| r.0 == fd) {
+                Some(r) => r.1 |= events,
+                None => {
+                    let ctx = self.watches.get(&fd).and_then(|w| w.1.as_ref());
+                    ready.push((fd, events, ctx));
+                }
+            }
+        }
+        Ok(ready)
+    }
+
+    /// 为 `fd` 提交一个过滤器变更。
+    fn change(&self, fd: i32, filter: i16, flags: u16) -> Result<(), SysError> {
+        unsafe {
+            let mut change: libc::kevent = std::mem::zeroed();
+            change.ident = fd as libc::uintptr_t;
+            change.filter = filter;
+            change.flags = flags;
+            let err = libc::kevent(
+                self.kq_fd,
+                &change,
+                1,
+                std::ptr::null_mut(),
+                0,
+                std::ptr::null(),
+            );
+            if err < 0 {
+                Err(SysError::last())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// 删除 `fd` 的一个过滤器，过滤器已不存在时视为成功。
+    fn delete(&self, fd: i32, filter: i16) -> Result<(), SysError> {
+        match self.change(fd, filter, libc::EV_DELETE) {
+            Err(err) if err.code() == libc::ENOENT => Ok(()),
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poller() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[0], Events::new().read(), None).is_ok());
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            let events = poller.pull_events(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].0, fds[1]);
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), 1);
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 2);
+            assert!(poller.modify(fds[1], Events::new().write(), None).is_ok());
+            assert!(poller.remove(fds[1]).is_ok());
+            assert!(poller.remove(fds[1]).is_err());
+            assert_eq!(
+                poller.modify(fds[1], Events::new().write(), None),
+                Err(SysError::from(libc::ENOENT))
+            );
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+}
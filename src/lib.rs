@@ -1,4 +1,10 @@
-﻿/// 定时事件枚举。
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+/// 定时事件枚举。
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Event {
     /// 没有事件。
@@ -15,6 +21,81 @@ pub enum Event {
     HangUp,
     /// 单次触发。
     OneShot,
+    /// 对端关闭写入。
+    ReadHangUp,
+    /// 紧急数据到达。
+    Priority,
+    /// 独占唤醒。
+    Exclusive,
+}
+
+impl Event {
+    /// 除 `None` 以外的所有事件。
+    const ALL: [Event; 9] = [
+        Event::Read,
+        Event::Write,
+        Event::Error,
+        Event::EdgeTriggered,
+        Event::HangUp,
+        Event::OneShot,
+        Event::ReadHangUp,
+        Event::Priority,
+        Event::Exclusive,
+    ];
+
+    /// 返回事件的小写名称。
+    pub fn name(self) -> &'static str {
+        match self {
+            Event::None => "none",
+            Event::Read => "read",
+            Event::Write => "write",
+            Event::Error => "error",
+            Event::EdgeTriggered => "edge",
+            Event::HangUp => "hangup",
+            Event::OneShot => "oneshot",
+            Event::ReadHangUp => "rdhup",
+            Event::Priority => "pri",
+            Event::Exclusive => "exclusive",
+        }
+    }
+
+    /// 从小写名称解析事件，未知名称返回 `None`。
+    pub fn from_name(name: &str) -> Option<Event> {
+        core::iter::once(Event::None)
+            .chain(Event::ALL.iter().copied())
+            .find(|event| event.name() == name)
+    }
+
+    /// 返回事件对应的 `libc::EPOLL*` 标志，`None` 返回 `0`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Event;
+    /// assert_eq!(Event::Read.as_epoll_flag(), libc::EPOLLIN as u32);
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn as_epoll_flag(self) -> u32 {
+        let flag = match self {
+            Event::None => 0,
+            Event::Read => libc::EPOLLIN,
+            Event::Write => libc::EPOLLOUT,
+            Event::Error => libc::EPOLLERR,
+            Event::EdgeTriggered => libc::EPOLLET,
+            Event::HangUp => libc::EPOLLHUP,
+            Event::OneShot => libc::EPOLLONESHOT,
+            Event::ReadHangUp => libc::EPOLLRDHUP,
+            Event::Priority => libc::EPOLLPRI,
+            Event::Exclusive => libc::EPOLLEXCLUSIVE,
+        };
+        flag as u32
+    }
+}
+
+impl core::fmt::Display for Event {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
 /// 定义事件集合。
@@ -28,16 +109,30 @@ pub enum Event {
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Events(u32);
 
-impl std::fmt::Display for Events {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "0x{:08X}", self.0)
+impl core::fmt::Display for Events {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_none() {
+            return write!(f, "Events(NONE)");
+        }
+        write!(f, "Events(")?;
+        for (i, event) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " | ")?;
+            }
+            // 使用 `Event::name` 的大写形式，两种显示共用同一份名称表。
+            use core::fmt::Write;
+            for c in event.name().chars() {
+                f.write_char(c.to_ascii_uppercase())?;
+            }
+        }
+        write!(f, ")")
     }
 }
 
 impl Events {
     /// 创建一个新的事件集合。
     pub fn new() -> Self {
-        Self { 0: 0 }
+        Self(0)
     }
 
     /// 清空当前值且返回一个空事件集合。
@@ -47,21 +142,178 @@ impl Events {
     }
 
     /// 附加数据到达事件到集合中。
-    pub fn read(mut self) -> Self {
-        self.0 |= 1 << Event::Read as u32;
-        self
+    pub fn read(self) -> Self {
+        self.with(Event::Read)
     }
 
     /// 附加目标可写事件到集合中。
-    pub fn write(mut self) -> Self {
-        self.0 |= 1 << Event::Write as u32;
-        self
+    pub fn write(self) -> Self {
+        self.with(Event::Write)
     }
 
     /// 附加发生错误事件到集合中。
-    pub fn error(mut self) -> Self {
-        self.0 |= 1 << Event::Error as u32;
-        self
+    pub fn error(self) -> Self {
+        self.with(Event::Error)
+    }
+
+    /// 附加对端关闭写入事件到集合中。
+    pub fn read_hangup(self) -> Self {
+        self.with(Event::ReadHangUp)
+    }
+
+    /// 附加紧急数据到达事件到集合中。
+    pub fn priority(self) -> Self {
+        self.with(Event::Priority)
+    }
+
+    /// 附加边沿触发标志到集合中。
+    ///
+    /// **注意：** 边沿触发模式下只在状态变化时通知一次，收到事件后必须持续读写 `fd`
+    /// 直到返回 `EAGAIN`，否则剩余的数据不会再次触发事件。
+    pub fn edge_triggered(self) -> Self {
+        self.with(Event::EdgeTriggered)
+    }
+
+    /// 附加单次触发标志到集合中。
+    ///
+    /// 事件触发一次后该 `fd` 即被禁用，需调用 `Poller::rearm()` 重新启用。
+    pub fn one_shot(self) -> Self {
+        self.with(Event::OneShot)
+    }
+
+    /// 附加独占唤醒标志到集合中。
+    ///
+    /// 多个 `Poller` 监视同一个 `fd`（例如监听套接字）时，事件只会唤醒其中一个，避免惊群效应。
+    ///
+    /// **注意：** 该标志只能在添加 `fd` 时使用，`Poller::modify()` 会返回 `EINVAL` 错误，
+    /// 且不能与单次触发标志同时使用。
+    pub fn exclusive(self) -> Self {
+        self.with(Event::Exclusive)
+    }
+
+    /// `cond` 为 `true` 时附加数据到达事件到集合中。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Events;
+    /// let (want_read, have_output) = (true, false);
+    /// let events = Events::new().read_if(want_read).write_if(have_output);
+    /// assert_eq!(events, Events::new().read());
+    /// ```
+    pub fn read_if(self, cond: bool) -> Self {
+        self.with_if(Event::Read, cond)
+    }
+
+    /// `cond` 为 `true` 时附加目标可写事件到集合中。
+    pub fn write_if(self, cond: bool) -> Self {
+        self.with_if(Event::Write, cond)
+    }
+
+    /// `cond` 为 `true` 时附加发生错误事件到集合中。
+    pub fn error_if(self, cond: bool) -> Self {
+        self.with_if(Event::Error, cond)
+    }
+
+    /// `cond` 为 `true` 时附加对端关闭写入事件到集合中。
+    pub fn read_hangup_if(self, cond: bool) -> Self {
+        self.with_if(Event::ReadHangUp, cond)
+    }
+
+    /// `cond` 为 `true` 时附加紧急数据到达事件到集合中。
+    pub fn priority_if(self, cond: bool) -> Self {
+        self.with_if(Event::Priority, cond)
+    }
+
+    /// `cond` 为 `true` 时附加边沿触发标志到集合中。
+    pub fn edge_triggered_if(self, cond: bool) -> Self {
+        self.with_if(Event::EdgeTriggered, cond)
+    }
+
+    /// `cond` 为 `true` 时附加单次触发标志到集合中。
+    pub fn one_shot_if(self, cond: bool) -> Self {
+        self.with_if(Event::OneShot, cond)
+    }
+
+    /// `cond` 为 `true` 时附加独占唤醒标志到集合中。
+    pub fn exclusive_if(self, cond: bool) -> Self {
+        self.with_if(Event::Exclusive, cond)
+    }
+
+    /// 从集合中移除数据到达事件。
+    pub fn without_read(self) -> Self {
+        self.without(Event::Read)
+    }
+
+    /// 从集合中移除目标可写事件。
+    pub fn without_write(self) -> Self {
+        self.without(Event::Write)
+    }
+
+    /// 从集合中移除发生错误事件。
+    pub fn without_error(self) -> Self {
+        self.without(Event::Error)
+    }
+
+    /// 从集合中移除对端关闭写入事件。
+    pub fn without_read_hangup(self) -> Self {
+        self.without(Event::ReadHangUp)
+    }
+
+    /// 从集合中移除紧急数据到达事件。
+    pub fn without_priority(self) -> Self {
+        self.without(Event::Priority)
+    }
+
+    /// 从集合中移除边沿触发标志。
+    pub fn without_edge_triggered(self) -> Self {
+        self.without(Event::EdgeTriggered)
+    }
+
+    /// 从集合中移除单次触发标志。
+    pub fn without_one_shot(self) -> Self {
+        self.without(Event::OneShot)
+    }
+
+    /// 从集合中移除独占唤醒标志。
+    pub fn without_exclusive(self) -> Self {
+        self.without(Event::Exclusive)
+    }
+
+    /// 附加 `event` 到集合中。
+    fn with(self, event: Event) -> Self {
+        self | Events::from(event)
+    }
+
+    /// `cond` 为 `true` 时附加 `event` 到集合中。
+    fn with_if(self, event: Event, cond: bool) -> Self {
+        if cond {
+            self.with(event)
+        } else {
+            self
+        }
+    }
+
+    /// 从集合中移除 `event`。
+    fn without(self, event: Event) -> Self {
+        self.difference(Events::from(event))
+    }
+
+    /// 返回集合原始值的十六进制表示，例如 `0x00000006`。
+    pub fn to_hex(self) -> String {
+        alloc::format!("0x{:08X}", self.0)
+    }
+
+    /// 返回集合中已设置的事件数量。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Events;
+    /// assert_eq!(Events::new().read().write().count(), 2);
+    /// ```
+    pub fn count(self) -> u32 {
+        self.0.count_ones()
     }
 
     /// 检查集合是否为空。
@@ -69,6 +321,81 @@ impl Events {
         self.0 == 0
     }
 
+    /// 返回一个遍历集合中所有已设置事件的迭代器。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::{Event, Events};
+    /// let events: Vec<Event> = Events::new().read().write().iter().collect();
+    /// assert_eq!(events, vec![Event::Read, Event::Write]);
+    /// ```
+    pub fn iter(self) -> impl Iterator<Item = Event> {
+        Event::ALL
+            .iter()
+            .copied()
+            .filter(move |&event| self.intersects(Events::from(event)))
+    }
+
+    /// 检查 `other` 中的所有事件是否都包含在集合中。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Events;
+    /// let reported = Events::new().read().write();
+    /// assert!(reported.contains(Events::new().read()));
+    /// assert!(!Events::new().read().contains(reported));
+    /// ```
+    pub fn contains(self, other: Events) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    /// 检查集合与 `other` 是否有任何共同的事件。
+    pub fn intersects(self, other: Events) -> bool {
+        (self.0 & other.0) != 0
+    }
+
+    /// 返回集合与 `other` 的并集，等同于 `self | other`。
+    pub fn union(self, other: Events) -> Events {
+        Self(self.0 | other.0)
+    }
+
+    /// 返回集合与 `other` 的交集，等同于 `self & other`。
+    pub fn intersection(self, other: Events) -> Events {
+        Self(self.0 & other.0)
+    }
+
+    /// 返回在集合中但不在 `other` 中的事件，等同于 `self & !other`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Events;
+    /// let reported = Events::new().read().write();
+    /// let handled = Events::new().read();
+    /// assert_eq!(reported.difference(handled), Events::new().write());
+    /// ```
+    pub fn difference(self, other: Events) -> Events {
+        Self(self.0 & !other.0)
+    }
+
+    /// 按照 `requested` 中关注的事件过滤内核报告的事件集合。
+    ///
+    /// 无论是否关注，内核总是会报告发生错误（`Event::Error`）及挂起（`Event::HangUp`）事件，
+    /// 因此这两个事件始终被保留，其余未在 `requested` 中的事件则被屏蔽。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::Events;
+    /// let reported = Events::new().read().write().error();
+    /// assert_eq!(reported.filtered(Events::new().read()), Events::new().read().error());
+    /// ```
+    pub fn filtered(self, requested: Events) -> Events {
+        self.intersection(requested.union(Events::new().error().with(Event::HangUp)))
+    }
+
     /// 检查集合是否有数据到达事件。
     pub fn has_read(self) -> bool {
         (self.0 & (1 << Event::Read as u32)) != 0
@@ -83,52 +410,690 @@ impl Events {
     pub fn has_error(self) -> bool {
         (self.0 & (1 << Event::Error as u32)) != 0
     }
+
+    /// 检查集合是否有已经挂起事件，即 `fd` 的两端均已关闭。
+    ///
+    /// 该事件由内核强制上报，无需在监视的事件集合中设置；设置时会如实转换为 `EPOLLHUP`，但不改变上报行为。
+    pub fn has_hangup(self) -> bool {
+        (self.0 & (1 << Event::HangUp as u32)) != 0
+    }
+
+    /// 检查集合是否有边沿触发标志。
+    pub fn has_edge_triggered(self) -> bool {
+        (self.0 & (1 << Event::EdgeTriggered as u32)) != 0
+    }
+
+    /// 检查集合是否有单次触发标志。
+    pub fn has_one_shot(self) -> bool {
+        (self.0 & (1 << Event::OneShot as u32)) != 0
+    }
+
+    /// 检查集合是否有对端关闭写入事件。
+    pub fn has_read_hangup(self) -> bool {
+        (self.0 & (1 << Event::ReadHangUp as u32)) != 0
+    }
+
+    /// 检查集合是否有独占唤醒标志。
+    pub fn has_exclusive(self) -> bool {
+        (self.0 & (1 << Event::Exclusive as u32)) != 0
+    }
+
+    /// 检查集合是否有紧急数据到达事件。
+    pub fn has_priority(self) -> bool {
+        (self.0 & (1 << Event::Priority as u32)) != 0
+    }
+}
+
+impl From<Event> for Events {
+    fn from(val: Event) -> Self {
+        match val {
+            Event::None => Self(0),
+            _ => Self(1 << val as u32),
+        }
+    }
+}
+
+/// 从逗号分隔的事件名称列表解析事件集合，例如 `"read, write"`。
+///
+/// 每个名称两端的空白会被忽略，空名称会被跳过，未知名称返回 `ParseEventsError`。
+///
+/// # Examples
+///
+/// ```
+/// use poller::Events;
+/// let events: Events = "read, write,edge".parse().unwrap();
+/// assert_eq!(events, Events::new().read().write().edge_triggered());
+/// assert!("read,bogus".parse::<Events>().is_err());
+/// ```
+impl core::str::FromStr for Events {
+    type Err = ParseEventsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| Event::from_name(name).ok_or_else(|| ParseEventsError(String::from(name))))
+            .collect()
+    }
+}
+
+/// 按子集关系比较事件集合：`a <= b` 当且仅当 `a` 中的事件都包含在 `b` 中。
+///
+/// 这是位集合上的偏序而不是数值比较，互不包含的集合（例如 `read` 与 `write`）无法比较，
+/// `partial_cmp` 返回 `None`，此时 `<`、`>`、`<=`、`>=` 均为 `false`。
+///
+/// # Examples
+///
+/// ```
+/// use poller::Events;
+/// let read = Events::new().read();
+/// let write = Events::new().write();
+/// assert!(read < read | write);
+/// assert_eq!(read.partial_cmp(&write), None);
+/// ```
+impl PartialOrd for Events {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        use core::cmp::Ordering;
+        if self == other {
+            Some(Ordering::Equal)
+        } else if other.contains(*self) {
+            Some(Ordering::Less)
+        } else if self.contains(*other) {
+            Some(Ordering::Greater)
+        } else {
+            None
+        }
+    }
+}
+
+impl core::iter::FromIterator<Event> for Events {
+    fn from_iter<I: IntoIterator<Item = Event>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(Events::new(), |events, event| events | Events::from(event))
+    }
+}
+
+impl core::ops::BitOr for Events {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl core::ops::BitOrAssign for Events {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl core::ops::BitAnd for Events {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl core::ops::BitAndAssign for Events {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl core::ops::Not for Events {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
+
+/// 定义解析事件集合时遇到未知名称的错误。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseEventsError(String);
+
+impl core::fmt::Display for ParseEventsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, r#"Unknown event name "{}""#, self.0)
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for ParseEventsError {}
+
 /// 定义系统错误。
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-pub struct SysError(i32);
+///
+/// 可以通过 `with_context` 附加产生错误的系统调用等上下文信息，比较两个错误时只比较错误码。
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SysError(i32, Option<&'static str>);
+
+impl PartialEq for SysError {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for SysError {}
 
-impl std::fmt::Display for SysError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, r#"Code={}, Reason="{{}}")"#, self.0)
+/// 与原始错误码比较，例如 `err == libc::EAGAIN`。
+impl PartialEq<i32> for SysError {
+    fn eq(&self, other: &i32) -> bool {
+        self.0 == *other
     }
 }
 
+impl PartialEq<SysError> for i32 {
+    fn eq(&self, other: &SysError) -> bool {
+        *self == other.0
+    }
+}
+
+impl core::fmt::Display for SysError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, r#"Code={}, Reason="{}""#, self.0, self.reason())?;
+        if let Some(context) = self.1 {
+            write!(f, r#", Context="{}""#, context)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
 impl std::error::Error for SysError {}
 
 impl From<i32> for SysError {
     fn from(val: i32) -> Self {
-        Self { 0: val }
+        Self(val, None)
     }
 }
 
-impl Into<i32> for SysError {
-    fn into(self) -> i32 {
-        self.0
+impl From<SysError> for i32 {
+    fn from(val: SysError) -> Self {
+        val.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<SysError> for std::io::Error {
+    fn from(val: SysError) -> Self {
+        std::io::Error::from_raw_os_error(val.code())
+    }
+}
+
+/// 从 `std::io::Error` 转换，没有系统错误码的错误会被转换为 `EIO`。
+#[cfg(feature = "std")]
+impl From<std::io::Error> for SysError {
+    fn from(val: std::io::Error) -> Self {
+        Self(val.raw_os_error().unwrap_or(libc::EIO), None)
     }
 }
 
 impl SysError {
+    /// 操作需要阻塞（`EAGAIN`），例如非阻塞 `fd` 上没有可读的数据。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::SysError;
+    /// let err = SysError::from(libc::EAGAIN);
+    /// assert_eq!(err, SysError::WOULD_BLOCK);
+    /// assert!(err == libc::EAGAIN);
+    /// ```
+    pub const WOULD_BLOCK: SysError = SysError(libc::EAGAIN, None);
+
+    /// 系统调用被信号中断（`EINTR`）。
+    pub const INTERRUPTED: SysError = SysError(libc::EINTR, None);
+
+    /// 对象不存在（`ENOENT`），例如 `fd` 不在监视列表中。
+    pub const NOT_FOUND: SysError = SysError(libc::ENOENT, None);
+
+    /// 对象已存在（`EEXIST`）。
+    pub const ALREADY_EXISTS: SysError = SysError(libc::EEXIST, None);
+
+    /// 无效的文件描述符（`EBADF`）。
+    pub const BAD_FD: SysError = SysError(libc::EBADF, None);
+
+    /// 无效的参数（`EINVAL`）。
+    pub const INVALID_INPUT: SysError = SysError(libc::EINVAL, None);
+
     /// 从系统当前 errno 创建一个 SysError 对象。
+    #[cfg(feature = "std")]
     pub fn last() -> Self {
-        unsafe {
-            Self {
-                0: *(libc::__errno_location()),
-            }
+        Self(
+            std::io::Error::last_os_error().raw_os_error().unwrap_or(0),
+            None,
+        )
+    }
+
+    /// 从系统当前 errno 创建一个 SysError 对象。
+    #[cfg(all(not(feature = "std"), target_os = "linux"))]
+    pub fn last() -> Self {
+        Self(unsafe { *libc::__errno_location() }, None)
+    }
+
+    /// 附加一个描述错误来源的上下文，例如 `"epoll_ctl ADD"`，会显示在 `Display` 的输出中。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::SysError;
+    /// let err = SysError::from(libc::EBADF).with_context("epoll_wait");
+    /// assert_eq!(err.context(), Some("epoll_wait"));
+    /// assert!(err.to_string().ends_with(r#"Context="epoll_wait""#));
+    /// ```
+    pub fn with_context(mut self, context: &'static str) -> Self {
+        self.1 = Some(context);
+        self
+    }
+
+    /// 返回附加的上下文，没有时返回 `None`。
+    pub fn context(&self) -> Option<&'static str> {
+        self.1
+    }
+
+    /// 返回系统错误码（errno）。
+    pub fn code(&self) -> i32 {
+        self.0
+    }
+
+    /// 返回错误码对应的 `std::io::ErrorKind` 分类。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poller::SysError;
+    /// let err = SysError::from(libc::EAGAIN);
+    /// assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn kind(&self) -> std::io::ErrorKind {
+        std::io::Error::from_raw_os_error(self.0).kind()
+    }
+
+    /// 检查错误是否为系统调用被信号中断（`EINTR`）。
+    pub fn is_interrupted(&self) -> bool {
+        self.0 == libc::EINTR
+    }
+
+    /// 返回错误码对应的描述信息。
+    fn reason(&self) -> String {
+        let mut buf = [0 as libc::c_char; 256];
+        let err = unsafe { libc::strerror_r(self.0, buf.as_mut_ptr(), buf.len()) };
+        if err != 0 {
+            return alloc::format!("Unknown error {}", self.0);
         }
+        unsafe { core::ffi::CStr::from_ptr(buf.as_ptr()) }
+            .to_string_lossy()
+            .into_owned()
     }
 }
 
+/// 定义 I/O 事件处理器，用于 `Poller::run` 事件循环的分发。
+///
+/// 所有 `FnMut(i32, Events) -> bool` 闭包都自动实现了该特征。
+pub trait EventHandler {
+    /// 处理 `fd` 上发生的事件，返回 `false` 时停止事件循环。
+    fn handle(&mut self, fd: i32, events: Events) -> bool;
+}
+
+impl<F> EventHandler for F
+where
+    F: FnMut(i32, Events) -> bool,
+{
+    fn handle(&mut self, fd: i32, events: Events) -> bool {
+        self(fd, events)
+    }
+}
+
+/// 将 `Duration` 转换为 `timespec`，秒数超出范围时饱和截断。
 #[cfg(target_os = "linux")]
+pub(crate) fn to_timespec(d: core::time::Duration) -> libc::timespec {
+    libc::timespec {
+        tv_sec: d.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+        tv_nsec: d.subsec_nanos() as libc::c_long,
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+#[cfg(all(target_os = "linux", not(feature = "backend-poll")))]
 pub mod epoll;
 
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", not(feature = "backend-poll")))]
+#[doc(inline)]
+pub use epoll::{
+    CancellableStatus, EventContext, EventData, EventIter, FdMap, Poller, Ready, ReadyEvent,
+    Registration, TokenEventData, TypedPoller, WaitStatus,
+};
+
+#[cfg(all(feature = "std", target_os = "linux", not(feature = "backend-poll")))]
 #[doc(inline)]
-pub use epoll::{EventContext, EventData, Poller};
+pub use epoll::Readable;
 
-#[cfg(not(target_os = "linux"))]
+#[cfg(all(target_os = "linux", not(feature = "backend-poll"), feature = "stats"))]
+#[doc(inline)]
+pub use epoll::PollerStats;
+
+#[cfg(all(
+    feature = "mio-compat",
+    target_os = "linux",
+    not(feature = "backend-poll")
+))]
+pub mod mio_compat;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod evdev;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+#[doc(inline)]
+pub use evdev::EvDev;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod eventfd;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+#[doc(inline)]
+pub use eventfd::EventFd;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod inotify;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+#[doc(inline)]
+pub use inotify::{Inotify, InotifyEvent};
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod signal;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+#[doc(inline)]
+pub use signal::SignalFd;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod timer;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+#[doc(inline)]
+pub use timer::Timer;
+
+#[cfg(all(feature = "std", any(target_os = "macos", target_os = "freebsd")))]
+pub mod kqueue;
+
+#[cfg(all(
+    feature = "std",
+    any(target_os = "macos", target_os = "freebsd"),
+    not(feature = "backend-poll")
+))]
+#[doc(inline)]
+pub use kqueue::{EventContext, EventData, Poller};
+
+#[cfg(all(
+    feature = "std",
+    not(any(target_os = "linux", target_os = "macos", target_os = "freebsd"))
+))]
 pub mod select;
 
-#[cfg(test)]
-mod tests {}
+#[cfg(all(
+    feature = "std",
+    not(any(target_os = "linux", target_os = "macos", target_os = "freebsd")),
+    not(feature = "backend-poll")
+))]
+#[doc(inline)]
+pub use select::{EventContext, EventData, Poller};
+
+#[cfg(all(feature = "std", feature = "backend-poll"))]
+pub mod poll;
+
+#[cfg(all(feature = "std", feature = "backend-poll"))]
+#[doc(inline)]
+pub use poll::{EventContext, EventData, Poller, TypedPoller};
+
+/// 当前 `Poller` 使用的事件通知后端名称：`"epoll"`、`"kqueue"`、`"select"` 或 `"poll"`。
+///
+/// # Examples
+///
+/// ```
+/// println!("Backend={}", poller::BACKEND);
+/// ```
+#[cfg(all(target_os = "linux", not(feature = "backend-poll")))]
+pub const BACKEND: &str = "epoll";
+
+/// 当前 `Poller` 使用的事件通知后端名称：`"epoll"`、`"kqueue"`、`"select"` 或 `"poll"`。
+#[cfg(all(
+    feature = "std",
+    any(target_os = "macos", target_os = "freebsd"),
+    not(feature = "backend-poll")
+))]
+pub const BACKEND: &str = "kqueue";
+
+/// 当前 `Poller` 使用的事件通知后端名称：`"epoll"`、`"kqueue"`、`"select"` 或 `"poll"`。
+#[cfg(all(
+    feature = "std",
+    not(any(target_os = "linux", target_os = "macos", target_os = "freebsd")),
+    not(feature = "backend-poll")
+))]
+pub const BACKEND: &str = "select";
+
+/// 当前 `Poller` 使用的事件通知后端名称：`"epoll"`、`"kqueue"`、`"select"` 或 `"poll"`。
+#[cfg(all(feature = "std", feature = "backend-poll"))]
+pub const BACKEND: &str = "poll";
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_ops() {
+        let read = Events::new().read();
+        let write = Events::new().write();
+        let mut events = read | write;
+        assert!(events.has_read() && events.has_write());
+        assert_eq!(events & read, read);
+        events &= !read;
+        assert_eq!(events, write);
+        events |= read;
+        assert_eq!(events, Events::new().read().write());
+    }
+
+    #[test]
+    fn test_events_contains() {
+        let events = Events::new().read().write();
+        assert!(events.contains(Events::new()));
+        assert!(events.contains(Events::new().write()));
+        assert!(!events.contains(Events::new().write().error()));
+        assert!(events.intersects(Events::new().write().error()));
+        assert!(!events.intersects(Events::new().error()));
+    }
+
+    #[test]
+    fn test_events_set_ops() {
+        let read = Events::new().read();
+        let rw = Events::new().read().write();
+        assert_eq!(read.union(Events::new().write()), rw);
+        assert_eq!(rw.intersection(read), read);
+        assert_eq!(rw.difference(read), Events::new().write());
+        assert!(read.difference(rw).is_none());
+        assert_eq!(rw.count(), 2);
+        assert_eq!(Events::new().count(), 0);
+    }
+
+    #[test]
+    fn test_backend() {
+        #[cfg(feature = "backend-poll")]
+        assert_eq!(BACKEND, "poll");
+        #[cfg(all(target_os = "linux", not(feature = "backend-poll")))]
+        assert_eq!(BACKEND, "epoll");
+        assert!(["epoll", "kqueue", "select", "poll"].contains(&BACKEND));
+    }
+
+    #[test]
+    fn test_events_if() {
+        assert_eq!(
+            Events::new().read_if(false).write_if(true),
+            Events::new().write()
+        );
+        assert_eq!(
+            Events::new()
+                .error_if(true)
+                .read_hangup_if(true)
+                .priority_if(false),
+            Events::new().error().read_hangup()
+        );
+        assert_eq!(
+            Events::new()
+                .edge_triggered_if(true)
+                .one_shot_if(false)
+                .exclusive_if(true),
+            Events::new().edge_triggered().exclusive()
+        );
+        assert_eq!(Events::new().read().read_if(false), Events::new().read());
+    }
+
+    #[test]
+    fn test_events_filtered() {
+        let requested = Events::new().read();
+        let reported = Events::new().read().write().with(Event::HangUp);
+        assert_eq!(
+            reported.filtered(requested),
+            Events::new().read().with(Event::HangUp)
+        );
+        assert_eq!(Events::new().write().filtered(requested), Events::new());
+        assert_eq!(
+            Events::new().error().filtered(Events::new()),
+            Events::new().error()
+        );
+    }
+
+    #[test]
+    fn test_events_without() {
+        let events = Events::new().read().write().edge_triggered();
+        assert_eq!(
+            events.without_write(),
+            Events::new().read().edge_triggered()
+        );
+        assert_eq!(events.without_error(), events);
+        let events = events
+            .without_read()
+            .without_write()
+            .without_edge_triggered();
+        assert!(events.is_none());
+    }
+
+    #[test]
+    fn test_events_partial_ord() {
+        let read = Events::new().read();
+        let write = Events::new().write();
+        let rw = read | write;
+        assert!(Events::new() < read);
+        assert!(read < rw && write <= rw && rw > write);
+        assert_eq!(rw.partial_cmp(&rw), Some(std::cmp::Ordering::Equal));
+        assert_eq!(
+            (read < write, read > write, read <= write),
+            (false, false, false)
+        );
+        assert_eq!(read.partial_cmp(&write), None);
+    }
+
+    #[test]
+    fn test_events_display() {
+        assert_eq!(Events::new().to_string(), "Events(NONE)");
+        assert_eq!(Events::new().read().to_string(), "Events(READ)");
+        assert_eq!(
+            Events::new().write().read().to_string(),
+            "Events(READ | WRITE)"
+        );
+        assert_eq!(Events::new().read().write().to_hex(), "0x00000006");
+    }
+
+    #[test]
+    fn test_event_display() {
+        assert_eq!(Event::Read.to_string(), "read");
+        assert_eq!(Event::EdgeTriggered.to_string(), "edge");
+        assert_eq!(
+            Events::new().edge_triggered().read_hangup().to_string(),
+            "Events(EDGE | RDHUP)"
+        );
+        assert_eq!(format!("{:?}", Event::ReadHangUp), "ReadHangUp");
+    }
+
+    #[test]
+    fn test_events_from_str() {
+        let all = Events::new()
+            .read()
+            .write()
+            .error()
+            .edge_triggered()
+            .one_shot()
+            .read_hangup()
+            .priority();
+        assert_eq!("read,write,error,edge,oneshot,rdhup,pri".parse(), Ok(all));
+        assert_eq!(" read , write ".parse(), Ok(Events::new().read().write()));
+        assert_eq!("".parse(), Ok(Events::new()));
+        let err = "read,foo".parse::<Events>().unwrap_err();
+        assert_eq!(err.to_string(), r#"Unknown event name "foo""#);
+    }
+
+    #[test]
+    fn test_sys_error_display() {
+        let err = SysError::from(libc::EACCES);
+        let msg = err.to_string();
+        assert!(msg.starts_with(&format!("Code={}, ", libc::EACCES)));
+        assert!(msg.contains("Permission denied"));
+    }
+
+    #[test]
+    fn test_sys_error_eq_code() {
+        let err = SysError::from(libc::ENOENT).with_context("epoll_ctl MOD");
+        assert!(err == libc::ENOENT);
+        assert!(libc::ENOENT == err);
+        assert!(err != libc::EAGAIN);
+        assert_eq!(err, SysError::NOT_FOUND);
+        assert_eq!(SysError::WOULD_BLOCK, libc::EAGAIN);
+        assert_eq!(SysError::INTERRUPTED.code(), libc::EINTR);
+        assert!(SysError::INTERRUPTED.is_interrupted());
+    }
+
+    #[test]
+    fn test_sys_error_context() {
+        let err = SysError::from(libc::ENOENT).with_context("epoll_ctl DEL");
+        assert_eq!(err, SysError::from(libc::ENOENT));
+        assert_eq!(err.context(), Some("epoll_ctl DEL"));
+        assert!(err.to_string().ends_with(r#", Context="epoll_ctl DEL""#));
+        assert_eq!(SysError::from(libc::ENOENT).context(), None);
+    }
+
+    #[test]
+    fn test_sys_error_kind() {
+        use std::io::ErrorKind;
+        assert_eq!(
+            SysError::from(libc::EWOULDBLOCK).kind(),
+            ErrorKind::WouldBlock
+        );
+        assert_eq!(SysError::from(libc::EINTR).kind(), ErrorKind::Interrupted);
+        assert_eq!(SysError::from(libc::ENOENT).kind(), ErrorKind::NotFound);
+        assert_eq!(SysError::from(libc::ENOENT).code(), libc::ENOENT);
+        assert!(SysError::from(libc::EINTR).is_interrupted());
+        assert!(!SysError::from(libc::EAGAIN).is_interrupted());
+    }
+
+    #[test]
+    fn test_sys_error_io_error() {
+        let err = std::io::Error::from(SysError::from(libc::ENOENT));
+        assert_eq!(err.raw_os_error(), Some(libc::ENOENT));
+        assert_eq!(SysError::from(err), SysError::from(libc::ENOENT));
+        let err = std::io::Error::other("custom");
+        assert_eq!(SysError::from(err), SysError::from(libc::EIO));
+    }
+
+    #[test]
+    fn test_events_from_event() {
+        assert_eq!(Events::from(Event::None), Events::new());
+        assert_eq!(Events::from(Event::Read), Events::new().read());
+        let events = vec![Event::Read, Event::Write, Event::None]
+            .into_iter()
+            .collect::<Events>();
+        assert_eq!(events, Events::new().read().write());
+    }
+}
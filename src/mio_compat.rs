@@ -0,0 +1,170 @@
+//! 与 `mio` 的 `Token` 及 `Interest` 互通的注册适配层。
+//!
+//! `Registry` 以 `mio::Registry` 的方式（令牌 + 关注事件）向 `Poller` 注册文件描述符，
+//! 便于将基于 `mio` 编写的代码迁移过来。与 `mio` 一致，所有注册均为边沿触发模式。
+use crate::{Events, Poller, SysError};
+use mio::{Interest, Token};
+use std::convert::TryFrom;
+use std::os::unix::io::AsRawFd;
+
+impl From<Interest> for Events {
+    fn from(interest: Interest) -> Self {
+        let mut events = Events::new();
+        if interest.is_readable() {
+            events = events.read();
+        }
+        if interest.is_writable() {
+            events = events.write();
+        }
+        if interest.is_priority() {
+            events = events.priority();
+        }
+        events
+    }
+}
+
+impl TryFrom<Events> for Interest {
+    type Error = SysError;
+
+    /// 转换集合中的读、写及紧急数据事件，不含任何一种时返回 `EINVAL` 错误。
+    fn try_from(events: Events) -> Result<Self, Self::Error> {
+        let mut interest: Option<Interest> = None;
+        let mut push = |other: Interest| {
+            interest = Some(interest.map_or(other, |i| i.add(other)));
+        };
+        if events.has_read() {
+            push(Interest::READABLE);
+        }
+        if events.has_write() {
+            push(Interest::WRITABLE);
+        }
+        if events.has_priority() {
+            push(Interest::PRIORITY);
+        }
+        interest.ok_or_else(|| SysError::from(libc::EINVAL))
+    }
+}
+
+/// 以 `mio::Registry` 的方式操作 `Poller` 的注册适配器。
+///
+/// # Examples
+///
+/// ```
+/// use mio::{Interest, Token};
+/// use poller::mio_compat::Registry;
+/// use poller::Poller;
+/// let mut poller = Poller::new().unwrap();
+/// let mut registry = Registry::new(&mut poller);
+/// registry.register(&std::io::stdout(), Token(7), Interest::WRITABLE).unwrap();
+/// registry.deregister(&std::io::stdout()).unwrap();
+/// ```
+pub struct Registry<'a> {
+    poller: &'a mut Poller,
+}
+
+impl<'a> Registry<'a> {
+    /// 创建一个操作 `poller` 的注册适配器。
+    pub fn new(poller: &'a mut Poller) -> Self {
+        Self { poller }
+    }
+
+    /// 以 `token` 及 `interest` 注册 `source`，已注册时返回 `EEXIST` 错误。
+    pub fn register<S: AsRawFd + ?Sized>(
+        &mut self,
+        source: &S,
+        token: Token,
+        interest: Interest,
+    ) -> Result<(), SysError> {
+        let fd = source.as_raw_fd();
+        if self.poller.contains(fd) {
+            return Err(SysError::from(libc::EEXIST));
+        }
+        self.poller
+            .add_with_token(fd, to_events(interest), token.0 as u64, None)
+    }
+
+    /// 更新已注册的 `source` 的令牌及关注事件，未注册时返回 `ENOENT` 错误。
+    pub fn reregister<S: AsRawFd + ?Sized>(
+        &mut self,
+        source: &S,
+        token: Token,
+        interest: Interest,
+    ) -> Result<(), SysError> {
+        let fd = source.as_raw_fd();
+        if !self.poller.contains(fd) {
+            return Err(SysError::from(libc::ENOENT));
+        }
+        self.poller
+            .add_with_token(fd, to_events(interest), token.0 as u64, None)
+    }
+
+    /// 注销 `source`，未注册时返回 `ENOENT` 错误。
+    pub fn deregister<S: AsRawFd + ?Sized>(&mut self, source: &S) -> Result<(), SysError> {
+        self.poller.remove(source.as_raw_fd()).map(|_| ())
+    }
+
+    /// 拉取所有被监测到的 I/O 事件，并以注册时的 `Token` 标识每个事件。
+    pub fn poll(&self, timeout_ms: i32) -> Result<Vec<(Token, Events)>, SysError> {
+        Ok(self
+            .poller
+            .pull_events_with_tokens(timeout_ms)?
+            .into_iter()
+            .map(|(token, _fd, events, _ctx)| (Token(token as usize), events))
+            .collect())
+    }
+}
+
+fn to_events(interest: Interest) -> Events {
+    Events::from(interest).edge_triggered()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interest_conversion() {
+        let interest = Interest::READABLE.add(Interest::WRITABLE);
+        assert_eq!(Events::from(interest), Events::new().read().write());
+        assert_eq!(
+            Interest::try_from(Events::new().read().write()),
+            Ok(interest)
+        );
+        assert_eq!(
+            Interest::try_from(Events::new().error()),
+            Err(SysError::from(libc::EINVAL))
+        );
+    }
+
+    #[test]
+    fn test_registry() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            let mut registry = Registry::new(&mut poller);
+            assert_eq!(
+                registry.reregister(&fds[1], Token(3), Interest::WRITABLE),
+                Err(SysError::from(libc::ENOENT))
+            );
+            assert!(registry
+                .register(&fds[1], Token(3), Interest::WRITABLE)
+                .is_ok());
+            assert_eq!(
+                registry.register(&fds[1], Token(3), Interest::WRITABLE),
+                Err(SysError::from(libc::EEXIST))
+            );
+            // 令牌与其它 `fd` 的编号或令牌相同都不影响注册。
+            assert!(registry
+                .register(&fds[0], Token(fds[1] as usize), Interest::READABLE)
+                .is_ok());
+            let ready = registry.poll(1000).unwrap();
+            assert_eq!(ready.len(), 1);
+            assert_eq!(ready[0].0, Token(3));
+            assert!(ready[0].1.has_write());
+            assert!(registry.deregister(&fds[1]).is_ok());
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+}
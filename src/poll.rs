@@ -0,0 +1,256 @@
+//! 基于 `poll(2)` 的可移植 I/O 事件通知。
+//!
+//! 通过 `backend-poll` 特性启用，用于没有 epoll 的受限内核；与 `select` 不同，它没有 `FD_SETSIZE` 限制。
+use crate::{Event, EventHandler, Events, SysError};
+use std::any::Any;
+use std::collections::HashMap;
+use std::os::unix::io::AsRawFd;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 定义事件关联上下文。
+pub type EventContext = Arc<dyn Any + Send + Sync>;
+
+/// 定义事件数据。
+///
+/// # Fields
+/// * `0` - 触发的文件描述符。
+/// * `1` - 触发的事件集合。
+/// * `2` - 触发的事件对应上下文。
+pub type EventData<'a, C = EventContext> = (i32, Events, Option<&'a C>);
+
+/// 定义文件 I/O 事件通知器，可以为每个 `fd` 关联任意类型的上下文。
+pub type Poller = TypedPoller<EventContext>;
+
+/// 定义上下文类型为 `C` 的文件 I/O 事件通知器。
+///
+/// 每个实例可以管理多个 `fd` 的 I/O 事件，每次拉取时都会根据监视列表重建 `pollfd` 数组。
+#[derive(Debug)]
+pub struct TypedPoller<C> {
+    watches: HashMap<i32, (Events, Option<C>)>,
+}
+
+impl<C> Default for TypedPoller<C> {
+    fn default() -> Self {
+        Self {
+            watches: HashMap::new(),
+        }
+    }
+}
+
+impl<C> TypedPoller<C> {
+    /// 创建一个新的 I/O 事件通知器。
+    pub fn new() -> Result<Self, SysError> {
+        Ok(Self::default())
+    }
+
+    /// 添加一个文件描述符到监视列表中。
+    ///
+    /// 如果 `fd` 已经在监视列表中，则更新其事件集合及上下文。
+    ///
+    /// **注意：** 此函数不会把 `fd` 的所有权转移到 `Poller` 内，请确保在 `Poller` 活动期内 `fd` 都是可用的。
+    pub fn add(&mut self, fd: i32, events: Events, ctx: Option<C>) -> Result<(), SysError> {
+        if fd < 0 {
+            return Err(SysError::from(libc::EBADF));
+        }
+        self.watches.insert(fd, (events, ctx));
+        Ok(())
+    }
+
+    /// 添加一个实现了 `AsRawFd` 的对象到监视列表中。
+    ///
+    /// **注意：** 与 `add` 相同，`Poller` 不会持有 `src`，请确保在 `Poller` 活动期内 `src` 都是可用的。
+    pub fn add_source<T: AsRawFd>(
+        &mut self,
+        src: &T,
+        events: Events,
+        ctx: Option<C>,
+    ) -> Result<(), SysError> {
+        self.add(src.as_raw_fd(), events, ctx)
+    }
+
+    /// 修改一个已在监视列表中的文件描述符的事件集合及上下文。
+    ///
+    /// 如果 `fd` 不在监视列表中则返回 `ENOENT` 错误。
+    pub fn modify(&mut self, fd: i32, events: Events, ctx: Option<C>) -> Result<(), SysError> {
+        if !self.watches.contains_key(&fd) {
+            return Err(SysError::from(libc::ENOENT));
+        }
+        self.add(fd, events, ctx)
+    }
+
+    /// 将一个文件描述符从监视列表中移除，并返回其关联的上下文（如果有）。
+    pub fn remove(&mut self, fd: i32) -> Result<Option<C>, SysError> {
+        match self.watches.remove(&fd) {
+            Some((_, ctx)) => Ok(ctx),
+            None => Err(SysError::from(libc::ENOENT)),
+        }
+    }
+
+    /// 将一个通过 `add_source` 添加的对象从监视列表中移除，与 `remove(src.as_raw_fd())` 相同。
+    pub fn remove_source<T: AsRawFd>(&mut self, src: &T) -> Result<Option<C>, SysError> {
+        self.remove(src.as_raw_fd())
+    }
+
+    /// 拉取所有被监测到的 I/O 事件。
+    ///
+    /// 等待过程被信号中断（`EINTR`）时会以剩余的超时时间自动重试。
+    pub fn pull_events(&self, timeout_ms: i32) -> Result<Vec<EventData<'_, C>>, SysError> {
+        let mut fds: Vec<libc::pollfd> = self
+            .watches
+            .iter()
+            .map(|(&fd, (events, _))| libc::pollfd {
+                fd,
+                events: to_poll_events(*events),
+                revents: 0,
+            })
+            .collect();
+        let start = Instant::now();
+        loop {
+            let remaining = if timeout_ms < 0 {
+                -1
+            } else {
+                let timeout = Duration::from_millis(timeout_ms as u64);
+                let remaining = timeout.checked_sub(start.elapsed()).unwrap_or_default();
+                remaining.as_millis() as i32
+            };
+            let n = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, remaining) };
+            if n >= 0 {
+                break;
+            }
+            let err = SysError::last();
+            if !err.is_interrupted() {
+                return Err(err);
+            }
+        }
+        Ok(fds
+            .iter()
+            .filter(|x| x.revents != 0)
+            .map(|x| {
+                let ctx = self.watches.get(&x.fd).and_then(|w| w.1.as_ref());
+                (x.fd, from_poll_events(x.revents), ctx)
+            })
+            .collect())
+    }
+
+    /// 运行事件循环，把每个就绪的 `fd` 分发给 `handlers` 中对应的处理器。
+    ///
+    /// 任意处理器返回 `false` 时，处理完当前这批事件后停止循环并返回 `Ok(())`。
+    pub fn run<H: EventHandler>(
+        &mut self,
+        handlers: &mut HashMap<i32, H>,
+        timeout_ms: i32,
+    ) -> Result<(), SysError> {
+        loop {
+            let mut running = true;
+            for (fd, events, _) in self.pull_events(timeout_ms)? {
+                if let Some(handler) = handlers.get_mut(&fd) {
+                    running &= handler.handle(fd, events);
+                }
+            }
+            if !running {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// 将事件集合转换为 `pollfd.events`。
+fn to_poll_events(events: Events) -> libc::c_short {
+    let mut val = 0;
+    if events.has_read() {
+        val |= libc::POLLIN;
+    }
+    if events.has_write() {
+        val |= libc::POLLOUT;
+    }
+    if events.has_priority() {
+        val |= libc::POLLPRI;
+    }
+    // `POLLRDHUP` 是 Linux 特有的标志，其它平台上无法单独关注对端关闭写入。
+    #[cfg(target_os = "linux")]
+    if events.has_read_hangup() {
+        val |= libc::POLLRDHUP;
+    }
+    val
+}
+
+/// 将 `pollfd.revents` 转换为事件集合。
+fn from_poll_events(val: libc::c_short) -> Events {
+    let mut events = Events::new();
+    if val & libc::POLLIN != 0 {
+        events = events.read();
+    }
+    if val & libc::POLLOUT != 0 {
+        events = events.write();
+    }
+    if val & libc::POLLPRI != 0 {
+        events = events.priority();
+    }
+    #[cfg(target_os = "linux")]
+    if val & libc::POLLRDHUP != 0 {
+        events = events.read_hangup();
+    }
+    if val & libc::POLLHUP != 0 {
+        events |= Events::from(Event::HangUp);
+    }
+    if val & (libc::POLLERR | libc::POLLNVAL) != 0 {
+        events = events.error();
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poller() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[0], Events::new().read(), None).is_ok());
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            let events = poller.pull_events(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].0, fds[1]);
+            assert!(events[0].1.has_write());
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), 1);
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 2);
+            assert!(poller.modify(fds[1], Events::new().write(), None).is_ok());
+            assert!(poller.remove(fds[1]).is_ok());
+            assert!(poller.remove(fds[1]).is_err());
+            assert_eq!(
+                poller.modify(fds[1], Events::new().write(), None),
+                Err(SysError::from(libc::ENOENT))
+            );
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_read_hangup() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(
+                libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()),
+                0
+            );
+            let mut poller = Poller::new().unwrap();
+            assert!(poller
+                .add(fds[0], Events::new().read_hangup(), None)
+                .is_ok());
+            assert!(poller.pull_events(0).unwrap().is_empty());
+            assert_eq!(libc::shutdown(fds[1], libc::SHUT_WR), 0);
+            let events = poller.pull_events(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert!(events[0].1.has_read_hangup());
+            assert_eq!(to_poll_events(Events::new().read_hangup()), libc::POLLRDHUP);
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+}
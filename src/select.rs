@@ -1 +1,222 @@
-﻿// TODO: Add select implements
+//! 基于 `select(2)` 的可移植 I/O 事件通知。
+//!
+//! 受 `FD_SETSIZE` 限制，只能监视编号小于 `FD_SETSIZE` 的文件描述符。
+use crate::{EventHandler, Events, SysError};
+use std::any::Any;
+use std::collections::HashMap;
+use std::os::unix::io::AsRawFd;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 定义事件关联上下文。
+pub type EventContext = Arc<dyn Any + Send + Sync>;
+
+/// 定义事件数据。
+///
+/// # Fields
+/// * `0` - 触发的文件描述符。
+/// * `1` - 触发的事件集合。
+/// * `2` - 触发的事件对应上下文。
+pub type EventData<'a> = (i32, Events, Option<&'a EventContext>);
+
+/// 定义文件 I/O 事件通知器。
+///
+/// 每个实例可以管理多个 `fd` 的 I/O 事件，读、写、错误事件分别对应 `select` 的
+/// `readfds`、`writefds` 和 `exceptfds` 集合。
+#[derive(Debug, Default)]
+pub struct Poller {
+    watches: HashMap<i32, (Events, Option<EventContext>)>,
+}
+
+impl Poller {
+    /// 创建一个新的 I/O 事件通知器。
+    pub fn new() -> Result<Self, SysError> {
+        Ok(Self::default())
+    }
+
+    /// 添加一个文件描述符到监视列表中。
+    ///
+    /// 如果 `fd` 已经在监视列表中，则更新其事件集合及上下文；`fd` 超出 `FD_SETSIZE` 时返回 `EINVAL` 错误。
+    ///
+    /// **注意：** 此函数不会把 `fd` 的所有权转移到 `Poller` 内，请确保在 `Poller` 活动期内 `fd` 都是可用的。
+    pub fn add(
+        &mut self,
+        fd: i32,
+        events: Events,
+        ctx: Option<EventContext>,
+    ) -> Result<(), SysError> {
+        if fd < 0 || fd as usize >= libc::FD_SETSIZE {
+            return Err(SysError::from(libc::EINVAL));
+        }
+        self.watches.insert(fd, (events, ctx));
+        Ok(())
+    }
+
+    /// 添加一个实现了 `AsRawFd` 的对象到监视列表中。
+    ///
+    /// **注意：** 与 `add` 相同，`Poller` 不会持有 `src`，请确保在 `Poller` 活动期内 `src` 都是可用的。
+    pub fn add_source<T: AsRawFd>(
+        &mut self,
+        src: &T,
+        events: Events,
+        ctx: Option<EventContext>,
+    ) -> Result<(), SysError> {
+        self.add(src.as_raw_fd(), events, ctx)
+    }
+
+    /// 修改一个已在监视列表中的文件描述符的事件集合及上下文。
+    ///
+    /// 如果 `fd` 不在监视列表中则返回 `ENOENT` 错误。
+    pub fn modify(
+        &mut self,
+        fd: i32,
+        events: Events,
+        ctx: Option<EventContext>,
+    ) -> Result<(), SysError> {
+        if !self.watches.contains_key(&fd) {
+            return Err(SysError::from(libc::ENOENT));
+        }
+        self.add(fd, events, ctx)
+    }
+
+    /// 将一个文件描述符从监视列表中移除，并返回其关联的上下文（如果有）。
+    pub fn remove(&mut self, fd: i32) -> Result<Option<EventContext>, SysError> {
+        match self.watches.remove(&fd) {
+            Some((_, ctx)) => Ok(ctx),
+            None => Err(SysError::from(libc::ENOENT)),
+        }
+    }
+
+    /// 将一个通过 `add_source` 添加的对象从监视列表中移除，与 `remove(src.as_raw_fd())` 相同。
+    pub fn remove_source<T: AsRawFd>(&mut self, src: &T) -> Result<Option<EventContext>, SysError> {
+        self.remove(src.as_raw_fd())
+    }
+
+    /// 拉取所有被监测到的 I/O 事件。
+    ///
+    /// 等待过程被信号中断（`EINTR`）时会以剩余的超时时间自动重试。
+    pub fn pull_events(&self, timeout_ms: i32) -> Result<Vec<EventData<'_>>, SysError> {
+        let start = Instant::now();
+        loop {
+            let remaining = if timeout_ms < 0 {
+                None
+            } else {
+                let timeout = Duration::from_millis(timeout_ms as u64);
+                Some(timeout.checked_sub(start.elapsed()).unwrap_or_default())
+            };
+            match self.select(remaining) {
+                Err(err) if err.is_interrupted() => continue,
+                result => return result,
+            }
+        }
+    }
+
+    /// 运行事件循环，把每个就绪的 `fd` 分发给 `handlers` 中对应的处理器。
+    ///
+    /// 任意处理器返回 `false` 时，处理完当前这批事件后停止循环并返回 `Ok(())`。
+    pub fn run<H: EventHandler>(
+        &mut self,
+        handlers: &mut HashMap<i32, H>,
+        timeout_ms: i32,
+    ) -> Result<(), SysError> {
+        loop {
+            let mut running = true;
+            for (fd, events, _) in self.pull_events(timeout_ms)? {
+                if let Some(handler) = handlers.get_mut(&fd) {
+                    running &= handler.handle(fd, events);
+                }
+            }
+            if !running {
+                return Ok(());
+            }
+        }
+    }
+
+    /// 调用一次 `select` 并收集就绪的事件。
+    fn select(&self, timeout: Option<Duration>) -> Result<Vec<EventData<'_>>, SysError> {
+        unsafe {
+            let mut readfds: libc::fd_set = std::mem::zeroed();
+            let mut writefds: libc::fd_set = std::mem::zeroed();
+            let mut exceptfds: libc::fd_set = std::mem::zeroed();
+            libc::FD_ZERO(&mut readfds);
+            libc::FD_ZERO(&mut writefds);
+            libc::FD_ZERO(&mut exceptfds);
+            let mut nfds = 0;
+            for (&fd, (events, _)) in self.watches.iter() {
+                if events.has_read() {
+                    libc::FD_SET(fd, &mut readfds);
+                }
+                if events.has_write() {
+                    libc::FD_SET(fd, &mut writefds);
+                }
+                if events.has_error() {
+                    libc::FD_SET(fd, &mut exceptfds);
+                }
+                nfds = nfds.max(fd + 1);
+            }
+            let mut tv = timeout.map(|d| libc::timeval {
+                tv_sec: d.as_secs() as libc::time_t,
+                tv_usec: d.subsec_micros() as libc::suseconds_t,
+            });
+            let tv_ptr = match tv {
+                Some(ref mut tv) => tv as *mut libc::timeval,
+                None => std::ptr::null_mut(),
+            };
+            let n = libc::select(nfds, &mut readfds, &mut writefds, &mut exceptfds, tv_ptr);
+            if n < 0 {
+                return Err(SysError::last());
+            }
+            let mut ready = Vec::with_capacity(n as usize);
+            for (&fd, (_, ctx)) in self.watches.iter() {
+                let mut events = Events::new();
+                if libc::FD_ISSET(fd, &readfds) {
+                    events = events.read();
+                }
+                if libc::FD_ISSET(fd, &writefds) {
+                    events = events.write();
+                }
+                if libc::FD_ISSET(fd, &exceptfds) {
+                    events = events.error();
+                }
+                if !events.is_none() {
+                    ready.push((fd, events, ctx.as_ref()));
+                }
+            }
+            Ok(ready)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poller() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let mut poller = Poller::new().unwrap();
+            assert!(poller.add(fds[0], Events::new().read(), None).is_ok());
+            assert!(poller.add(fds[1], Events::new().write(), None).is_ok());
+            let events = poller.pull_events(1000).unwrap();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].0, fds[1]);
+            assert!(events[0].1.has_write());
+            assert_eq!(libc::write(fds[1], b"x".as_ptr() as *const _, 1), 1);
+            assert_eq!(poller.pull_events(1000).unwrap().len(), 2);
+            assert!(poller.modify(fds[1], Events::new().write(), None).is_ok());
+            assert!(poller.remove(fds[1]).is_ok());
+            assert!(poller.remove(fds[1]).is_err());
+            assert_eq!(
+                poller.modify(fds[1], Events::new().write(), None),
+                Err(SysError::from(libc::ENOENT))
+            );
+            assert!(poller
+                .add(libc::FD_SETSIZE as i32, Events::new().read(), None)
+                .is_err());
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+}
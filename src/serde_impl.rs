@@ -0,0 +1,87 @@
+//! `Event` 与 `Events` 的序列化支持。
+//!
+//! `Event` 序列化为小写名称字符串，`Events` 序列化为名称列表，例如 `["read","write"]`。
+use crate::{Event, Events};
+use core::fmt;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+impl Serialize for Event {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Event {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EventVisitor;
+
+        impl<'de> Visitor<'de> for EventVisitor {
+            type Value = Event;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "an event flag name")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Event, E> {
+                Event::from_name(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+            }
+        }
+
+        deserializer.deserialize_str(EventVisitor)
+    }
+}
+
+impl Serialize for Events {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(None)?;
+        for event in self.iter() {
+            seq.serialize_element(&event)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Events {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EventsVisitor;
+
+        impl<'de> Visitor<'de> for EventsVisitor {
+            type Value = Events;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a list of event flag names")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Events, A::Error> {
+                let mut events = Events::new();
+                while let Some(event) = seq.next_element::<Event>()? {
+                    events |= Events::from(event);
+                }
+                Ok(events)
+            }
+        }
+
+        deserializer.deserialize_seq(EventsVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use serde::de::value::{Error, SeqDeserializer, StrDeserializer};
+    use serde::de::IntoDeserializer;
+
+    #[test]
+    fn test_deserialize() {
+        let de: StrDeserializer<Error> = "write".into_deserializer();
+        assert_eq!(Event::deserialize(de), Ok(Event::Write));
+        let de: StrDeserializer<Error> = "bogus".into_deserializer();
+        assert!(Event::deserialize(de).is_err());
+        let de: SeqDeserializer<_, Error> = vec!["read", "write"].into_deserializer();
+        assert_eq!(Events::deserialize(de), Ok(Events::new().read().write()));
+        let de: SeqDeserializer<_, Error> = vec!["read", "bogus"].into_deserializer();
+        assert!(Events::deserialize(de).is_err());
+    }
+}
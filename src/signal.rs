@@ -0,0 +1,97 @@
+//! Linux 信号文件描述符（signalfd）。
+//!
+use crate::SysError;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+
+/// 定义基于 `signalfd` 的信号源。
+///
+/// 创建时会在当前线程中阻塞指定的信号，使其不再由信号处理函数处理，而是在到达时使文件描述符变为可读，
+/// 可以通过 `Poller::add_source` 与其它 `fd` 一起监视。
+///
+/// **注意：** 信号掩码按线程生效，应在创建其它线程之前于主线程中创建，否则信号可能被投递到未阻塞它的线程；
+/// 销毁时不会恢复原来的信号掩码。
+///
+/// # Examples
+///
+/// ```no_run
+/// use poller::{Events, Poller, SignalFd};
+/// let signals = SignalFd::new(&[libc::SIGINT, libc::SIGTERM]).unwrap();
+/// let mut poller = Poller::new().unwrap();
+/// poller.add_source(&signals, Events::new().read(), None).unwrap();
+/// poller.pull_events(-1).unwrap();
+/// let info = signals.read_signal().unwrap();
+/// println!("Received signal {}", info.ssi_signo);
+/// ```
+#[derive(Debug)]
+pub struct SignalFd {
+    fd: OwnedFd,
+}
+
+impl AsRawFd for SignalFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl SignalFd {
+    /// 阻塞 `signals` 中的信号并创建一个接收它们的信号源。
+    pub fn new(signals: &[libc::c_int]) -> Result<Self, SysError> {
+        unsafe {
+            let mut mask: libc::sigset_t = std::mem::zeroed();
+            libc::sigemptyset(&mut mask);
+            for &signal in signals {
+                if libc::sigaddset(&mut mask, signal) < 0 {
+                    return Err(SysError::last());
+                }
+            }
+            let err = libc::pthread_sigmask(libc::SIG_BLOCK, &mask, std::ptr::null_mut());
+            if err != 0 {
+                return Err(SysError::from(err));
+            }
+            let fd = libc::signalfd(-1, &mask, libc::SFD_CLOEXEC | libc::SFD_NONBLOCK);
+            if fd < 0 {
+                return Err(SysError::last());
+            }
+            Ok(Self {
+                fd: OwnedFd::from_raw_fd(fd),
+            })
+        }
+    }
+
+    /// 读取一个已到达的信号，没有待处理的信号时返回 `EAGAIN` 错误。
+    pub fn read_signal(&self) -> Result<libc::signalfd_siginfo, SysError> {
+        unsafe {
+            let mut info: libc::signalfd_siginfo = std::mem::zeroed();
+            let size = std::mem::size_of::<libc::signalfd_siginfo>();
+            let n = libc::read(
+                self.as_raw_fd(),
+                &mut info as *mut libc::signalfd_siginfo as *mut _,
+                size,
+            );
+            if n < 0 {
+                Err(SysError::last())
+            } else {
+                Ok(info)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_fd() {
+        let signals = SignalFd::new(&[libc::SIGUSR2]).unwrap();
+        assert_eq!(
+            signals.read_signal().unwrap_err(),
+            SysError::from(libc::EAGAIN)
+        );
+        unsafe {
+            libc::pthread_kill(libc::pthread_self(), libc::SIGUSR2);
+        }
+        let info = signals.read_signal().unwrap();
+        assert_eq!(info.ssi_signo, libc::SIGUSR2 as u32);
+    }
+}
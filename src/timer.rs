@@ -0,0 +1,102 @@
+//! Linux 定时器文件描述符（timerfd）。
+//!
+use crate::{to_timespec, SysError};
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::time::Duration;
+
+/// 定义基于 `timerfd` 的定时器。
+///
+/// 定时器到期时其文件描述符变为可读，可以通过 `Poller::add_source` 与其它 `fd` 一起监视。
+///
+/// # Examples
+///
+/// ```
+/// use poller::{Events, Poller, Timer};
+/// use std::time::Duration;
+/// let timer = Timer::after(Duration::from_millis(10)).unwrap();
+/// let mut poller = Poller::new().unwrap();
+/// poller.add_source(&timer, Events::new().read(), None).unwrap();
+/// assert_eq!(poller.pull_events(1000).unwrap().len(), 1);
+/// assert_eq!(timer.read_expirations().unwrap(), 1);
+/// ```
+#[derive(Debug)]
+pub struct Timer {
+    fd: OwnedFd,
+}
+
+impl AsRawFd for Timer {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl Timer {
+    /// 创建一个在 `delay` 之后触发一次的定时器。
+    pub fn after(delay: Duration) -> Result<Self, SysError> {
+        Self::new(delay, Duration::from_secs(0))
+    }
+
+    /// 创建一个每隔 `period` 触发一次的周期定时器。
+    pub fn interval(period: Duration) -> Result<Self, SysError> {
+        Self::new(period, period)
+    }
+
+    fn new(value: Duration, interval: Duration) -> Result<Self, SysError> {
+        let fd = unsafe {
+            libc::timerfd_create(
+                libc::CLOCK_MONOTONIC,
+                libc::TFD_CLOEXEC | libc::TFD_NONBLOCK,
+            )
+        };
+        if fd < 0 {
+            return Err(SysError::last());
+        }
+        let timer = Self {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        };
+        // 初始值为 0 会解除定时器，因此至少设置为 1 纳秒。
+        let value = value.max(Duration::from_nanos(1));
+        let spec = libc::itimerspec {
+            it_interval: to_timespec(interval),
+            it_value: to_timespec(value),
+        };
+        let err = unsafe { libc::timerfd_settime(fd, 0, &spec, std::ptr::null_mut()) };
+        if err < 0 {
+            Err(SysError::last())
+        } else {
+            Ok(timer)
+        }
+    }
+
+    /// 读取并清零自上次读取以来定时器到期的次数，尚未到期时返回 `0`。
+    pub fn read_expirations(&self) -> Result<u64, SysError> {
+        let mut val = 0u64;
+        let n = unsafe { libc::read(self.as_raw_fd(), &mut val as *mut u64 as *mut _, 8) };
+        if n < 0 {
+            let err = SysError::last();
+            if err.code() == libc::EAGAIN {
+                Ok(0)
+            } else {
+                Err(err)
+            }
+        } else {
+            Ok(val)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timer() {
+        let timer = Timer::interval(Duration::from_millis(5)).unwrap();
+        assert_eq!(timer.read_expirations().unwrap(), 0);
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(timer.read_expirations().unwrap() >= 2);
+        let timer = Timer::after(Duration::from_secs(0)).unwrap();
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(timer.read_expirations().unwrap(), 1);
+    }
+}